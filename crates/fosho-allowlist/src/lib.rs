@@ -0,0 +1,180 @@
+//! Allowlist of an event, shared by the fosho program and its clients.
+//!
+//! Leaves commit to the wallet, its ticket tier and its discount. Pairs are
+//! hashed in sorted order so a proof is the list of sibling hashes only.
+
+use solana_program::{hash::hashv, pubkey::Pubkey};
+
+pub type Node = [u8; 32];
+
+/// domain separation between leaves and inner nodes
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+pub const MAX_DISCOUNT_BPS: u16 = 10_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllowlistEntry {
+  pub wallet: Pubkey,
+  /// the wallet can only join with this ticket tier if provided
+  pub tier: Option<u8>,
+  /// discount on the commitment fee, in basis points
+  pub discount_bps: u16,
+}
+
+impl AllowlistEntry {
+  pub fn leaf(&self) -> Node {
+    let tier = match self.tier {
+      Some(tier) => [1, tier],
+      None => [0, 0],
+    };
+    hashv(&[
+      LEAF_PREFIX,
+      self.wallet.as_ref(),
+      &tier,
+      &self.discount_bps.to_le_bytes(),
+    ])
+    .to_bytes()
+  }
+}
+
+pub fn hash_pair(left: &Node, right: &Node) -> Node {
+  let (first, second) = if left <= right {
+    (left, right)
+  } else {
+    (right, left)
+  };
+  hashv(&[NODE_PREFIX, first, second]).to_bytes()
+}
+
+pub fn verify_proof(root: &Node, leaf: Node, proof: &[Node]) -> bool {
+  let computed_root = proof
+    .iter()
+    .fold(leaf, |node, sibling| hash_pair(&node, sibling));
+  computed_root == *root
+}
+
+/// Every layer of the tree, from the leaves to the root.
+/// The last node of a layer with an odd length moves up unchanged.
+#[derive(Clone, Debug)]
+pub struct AllowlistTree {
+  entries: Vec<AllowlistEntry>,
+  layers: Vec<Vec<Node>>,
+}
+
+impl AllowlistTree {
+  /// returns None for an empty allowlist
+  pub fn new(entries: Vec<AllowlistEntry>) -> Option<Self> {
+    if entries.is_empty() {
+      return None;
+    }
+    let mut layers = vec![entries.iter().map(AllowlistEntry::leaf).collect::<Vec<_>>()];
+    while layers.last().unwrap().len() > 1 {
+      let next_layer = layers
+        .last()
+        .unwrap()
+        .chunks(2)
+        .map(|pair| match pair {
+          [left, right] => hash_pair(left, right),
+          [node] => *node,
+          _ => unreachable!(),
+        })
+        .collect();
+      layers.push(next_layer);
+    }
+    Some(Self { entries, layers })
+  }
+
+  pub fn root(&self) -> Node {
+    self.layers.last().unwrap()[0]
+  }
+
+  pub fn entries(&self) -> &[AllowlistEntry] {
+    &self.entries
+  }
+
+  pub fn proof(&self, index: usize) -> Option<Vec<Node>> {
+    if index >= self.entries.len() {
+      return None;
+    }
+    let mut proof = vec![];
+    let mut position = index;
+    for layer in &self.layers[..self.layers.len() - 1] {
+      if let Some(sibling) = layer.get(position ^ 1) {
+        proof.push(*sibling);
+      }
+      position /= 2;
+    }
+    Some(proof)
+  }
+
+  /// entry and proof of the first entry of the wallet
+  pub fn proof_for(&self, wallet: &Pubkey) -> Option<(&AllowlistEntry, Vec<Node>)> {
+    let index = self
+      .entries
+      .iter()
+      .position(|entry| entry.wallet == *wallet)?;
+    Some((&self.entries[index], self.proof(index)?))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entries(count: usize) -> Vec<AllowlistEntry> {
+    (0..count)
+      .map(|index| AllowlistEntry {
+        wallet: Pubkey::new_unique(),
+        tier: if index % 2 == 0 { None } else { Some(index as u8) },
+        discount_bps: (index as u16 * 1_000) % MAX_DISCOUNT_BPS,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn proves_every_entry() {
+    for count in 2..=9 {
+      let tree = AllowlistTree::new(entries(count)).unwrap();
+      for (index, entry) in tree.entries().iter().enumerate() {
+        let proof = tree.proof(index).unwrap();
+        assert!(verify_proof(&tree.root(), entry.leaf(), &proof));
+      }
+      assert_eq!(tree.proof(count), None);
+    }
+  }
+
+  #[test]
+  fn rejects_another_tier_or_discount() {
+    let tree = AllowlistTree::new(entries(5)).unwrap();
+    let (entry, proof) = tree.proof_for(&tree.entries()[1].wallet).unwrap();
+
+    let other_tier = AllowlistEntry {
+      tier: None,
+      ..entry.clone()
+    };
+    assert!(!verify_proof(&tree.root(), other_tier.leaf(), &proof));
+
+    let other_discount = AllowlistEntry {
+      discount_bps: entry.discount_bps + 1,
+      ..entry.clone()
+    };
+    assert!(!verify_proof(&tree.root(), other_discount.leaf(), &proof));
+  }
+
+  #[test]
+  fn proves_a_single_entry() {
+    let tree = AllowlistTree::new(entries(1)).unwrap();
+    let entry = &tree.entries()[0];
+    let proof = tree.proof(0).unwrap();
+
+    assert!(proof.is_empty());
+    assert_eq!(tree.root(), entry.leaf());
+    assert!(verify_proof(&tree.root(), entry.leaf(), &proof));
+  }
+
+  #[test]
+  fn rejects_an_empty_allowlist() {
+    assert!(AllowlistTree::new(vec![]).is_none());
+  }
+}
@@ -3,3 +3,27 @@ pub const EVENT_PRE_SEED: &[u8] = b"event";
 pub const ATTENDEE_PRE_SEED: &[u8] = b"attendee";
 pub const EVENT_COLLECTION_SUFFIX_SEED: &[u8] = b"collection";
 pub const TICKET_SUFFIX_SEED: &[u8] = b"ticket";
+pub const TICKET_TIER_SUFFIX_SEED: &[u8] = b"tier";
+pub const VAULT_SUFFIX_SEED: &[u8] = b"vault";
+pub const PROPOSAL_PRE_SEED: &[u8] = b"proposal";
+pub const MEMBER_PRE_SEED: &[u8] = b"member";
+pub const ATTENDANCE_PRE_SEED: &[u8] = b"attendance";
+pub const INVITATION_PRE_SEED: &[u8] = b"invitation";
+pub const SESSION_SUFFIX_SEED: &[u8] = b"session";
+pub const APPEAL_PRE_SEED: &[u8] = b"appeal";
+pub const CHECK_IN_CHALLENGE_PREFIX: &[u8] = b"fosho-check-in";
+pub const INVITATION_PREFIX: &[u8] = b"fosho-invitation";
+
+/// time after the settlement during which attendees can claim, 30 days.
+pub const CLAIM_PERIOD: i64 = 60 * 60 * 24 * 30;
+/// time after its creation during which a proposal can be approved and executed, 30 days.
+pub const PROPOSAL_LIFETIME: i64 = 60 * 60 * 24 * 30;
+pub const BPS_DENOMINATOR: u16 = 10_000;
+pub const MAX_EVENT_AUTHORITIES: usize = 4;
+
+/// permission bits of an event authority
+pub const ROLE_SCANNER: u8 = 1 << 0;
+pub const ROLE_APPROVER: u8 = 1 << 1;
+pub const ROLE_TREASURER: u8 = 1 << 2;
+pub const ROLE_EDITOR: u8 = 1 << 3;
+pub const ALL_ROLES: u8 = ROLE_SCANNER | ROLE_APPROVER | ROLE_TREASURER | ROLE_EDITOR;
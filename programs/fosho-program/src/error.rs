@@ -62,4 +62,146 @@ pub enum FoshoErrors {
   NotEnoughRemainingAccounts,
   #[msg("User may not have enough tokens or incorrect data has been supplied")]
   InvalidTokenDetails,
+  #[msg("Capacity cannot be lower than the number of registered attendees")]
+  InvalidCapacity,
+  #[msg("The commitment fee cannot be changed once tickets have been minted")]
+  CommitmentFeeLocked,
+  #[msg("The event status does not allow this action")]
+  InvalidEventStatus,
+  #[msg("Invalid event status transition")]
+  InvalidStatusTransition,
+  #[msg("Event can only be closed once every attendee claimed or the claim period ended")]
+  EventNotClosable,
+  #[msg("Invalid refund policy")]
+  InvalidRefundPolicy,
+  #[msg("A ticket tier has to be provided for this event")]
+  TicketTierRequired,
+  #[msg("Ticket tier does not belong to this event")]
+  InvalidTicketTier,
+  #[msg("Vault balance does not cover the fees still owed")]
+  VaultOutOfBalance,
+  #[msg("The forfeit policy cannot be changed once tickets have been minted")]
+  ForfeitPolicyLocked,
+  #[msg("Forfeit receiver does not match the forfeit policy")]
+  InvalidForfeitReceiver,
+  #[msg("Not enough reward tokens have been funded for this event")]
+  RewardsNotFunded,
+  #[msg("Amount exceeds the rewards not owed to attendees")]
+  RewardsAllocated,
+  #[msg("An event can have at most 4 event authorities")]
+  TooManyEventAuthorities,
+  #[msg("Event authority is already registered")]
+  DuplicateEventAuthority,
+  #[msg("Event authority not found")]
+  EventAuthorityNotFound,
+  #[msg("Invalid event authority roles")]
+  InvalidRoles,
+  #[msg("Event authority does not have the required role")]
+  MissingEventRole,
+  #[msg("Community name is too long")]
+  CommunityNameTooLong,
+  #[msg("Community metadata is too long")]
+  InvalidCommunityMetadata,
+  #[msg("Signer is not the pending community authority")]
+  InvalidPendingAuthority,
+  #[msg("Signer is not the community authority")]
+  InvalidAuthority,
+  #[msg("Invalid approver set or threshold")]
+  InvalidApprovers,
+  #[msg("Signer is not an approver of the community")]
+  NotAnApprover,
+  #[msg("Approver already approved this proposal")]
+  AlreadyApproved,
+  #[msg("An approved proposal is required for this action")]
+  ProposalRequired,
+  #[msg("Proposal does not match this action")]
+  ProposalActionMismatch,
+  #[msg("Proposal does not have enough approvals")]
+  ProposalNotApproved,
+  #[msg("Proposal has already been executed")]
+  ProposalAlreadyExecuted,
+  #[msg("Invalid member status")]
+  InvalidMemberStatus,
+  #[msg("Attendee is not an active member of the community")]
+  NotAMember,
+  #[msg("The asset is not an mpl-core asset owned by the attendee")]
+  InvalidCoreAsset,
+  #[msg("The asset does not carry the required attribute")]
+  InvalidAssetAttribute,
+  #[msg("Gate rules are malformed")]
+  InvalidGateRules,
+  #[msg("None of the alternative gate rules is satisfied")]
+  NoGateRuleSatisfied,
+  #[msg("The attendee matches an excluded gate rule")]
+  ExcludedByGateRule,
+  #[msg("Invalid attendance gate")]
+  InvalidAttendanceGate,
+  #[msg("The attendee has not attended enough past events of the community")]
+  NotEnoughPastAttendances,
+  #[msg("The gate of the event has no allowlist")]
+  NotAnAllowlistGate,
+  #[msg("The attendee is not on the allowlist")]
+  NotAllowlisted,
+  #[msg("The invitation does not match this join")]
+  InvalidInvitation,
+  #[msg("The invitation has expired")]
+  InvitationExpired,
+  #[msg("The invitation has been used too many times")]
+  InvitationUsedUp,
+  #[msg("The check-in challenge is too old")]
+  StaleCheckInChallenge,
+  #[msg("The expected Ed25519 signature is missing or invalid")]
+  InvalidSignature,
+  #[msg("Proof of presence check-ins have to be verified with verify_attendee")]
+  ProofOfPresenceRequired,
+  #[msg("Invalid settlement rule")]
+  InvalidSettlementRule,
+  #[msg("The settlement rule cannot change once attendees joined")]
+  SettlementRuleLocked,
+  #[msg("Attendees of this event are checked in per session")]
+  SessionCheckInRequired,
+  #[msg("Invalid session")]
+  InvalidSession,
+  #[msg("The session is not in progress")]
+  SessionNotInProgress,
+  #[msg("Invalid check-out policy")]
+  InvalidCheckOutPolicy,
+  #[msg("The check-out policy cannot change once attendees joined")]
+  CheckOutPolicyLocked,
+  #[msg("The event does not require a check-out")]
+  CheckOutNotRequired,
+  #[msg("The attendee has not checked in")]
+  NotCheckedIn,
+  #[msg("The check-out window is closed")]
+  CheckOutClosed,
+  #[msg("Scan results can only be amended to Verified or Rejected")]
+  InvalidAmendment,
+  #[msg("The amendment window of the event is closed")]
+  AmendmentWindowClosed,
+  #[msg("Invalid amendment window")]
+  InvalidAmendmentWindow,
+  #[msg("Only rejected attendees can appeal")]
+  NotRejected,
+  #[msg("The appeal has already been resolved")]
+  AppealAlreadyResolved,
+  #[msg("The reason of the appeal is too long")]
+  AppealReasonTooLong,
+  #[msg("The amendment window of the event is still open")]
+  AmendmentWindowOpen,
+  #[msg("The name or the uri of the ticket tier is too long")]
+  InvalidTicketTierMetadata,
+  #[msg("The event must end after it starts")]
+  InvalidEventEndTime,
+  #[msg("Registration start time cannot exceed the registration end time")]
+  InvalidRegistrationStartTime,
+  #[msg("The refund policy cannot change once attendees joined")]
+  RefundPolicyLocked,
+  #[msg("The proposal has expired")]
+  ProposalExpired,
+  #[msg("The proposal can still be executed")]
+  ProposalStillOpen,
+  #[msg("Signer is not the community authority or a moderator of the pending member")]
+  NotAModerator,
+  #[msg("Sessions cannot be added once attendees joined")]
+  SessionsLocked,
 }
@@ -0,0 +1,151 @@
+use crate::{
+  constant::*,
+  state::*,
+  utils::{write_ticket_app_data, TicketCpiAccounts},
+};
+use anchor_lang::prelude::*;
+
+use mpl_core::{
+  accounts::{BaseAssetV1, BaseCollectionV1},
+  types::UpdateAuthority,
+  ID as MPL_CORE_ID,
+};
+
+#[derive(Accounts)]
+pub struct AmendAttendance<'info> {
+  #[account(
+    mut,
+    seeds = [
+      ATTENDEE_PRE_SEED,
+      event.key().as_ref(),
+      attendee_record.owner.key().as_ref()
+    ],
+    bump = attendee_record.bump,
+    has_one = event,
+  )]
+  pub attendee_record: Box<Account<'info, Attendee>>,
+  #[account(
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
+  #[account(
+    mut,
+    seeds = [
+      ATTENDANCE_PRE_SEED,
+      community.key().as_ref(),
+      attendee_record.owner.as_ref()
+    ],
+    bump = attendance_history.bump,
+  )]
+  pub attendance_history: Box<Account<'info, AttendanceHistory>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED,
+        event.key().as_ref(),
+        EVENT_COLLECTION_SUFFIX_SEED,
+      ],
+      bump,
+      constraint = event_collection.update_authority == community.key(),
+  )]
+  pub event_collection: Box<Account<'info, BaseCollectionV1>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED,
+        event.key().as_ref(),
+        attendee_record.owner.key().as_ref(),
+        TICKET_SUFFIX_SEED,
+      ],
+      bump,
+      constraint = ticket.update_authority == UpdateAuthority::Collection(event_collection.key()),
+  )]
+  pub ticket: Box<Account<'info, BaseAssetV1>>,
+  /// community authority or an approver of the event
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+  /// CHECK: This is checked by the address constraint
+  #[account(address = MPL_CORE_ID)]
+  pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+impl<'info> AmendAttendance<'info> {
+  /// replaces the scan result, the ticket stays frozen. The session bitmap of
+  /// session events is kept in front of the result.
+  pub fn rewrite_ticket_scan(&self, status: &AttendeeStatus) -> Result<()> {
+    let result: &[u8] = if *status == AttendeeStatus::Verified {
+      b"Verified"
+    } else {
+      b"Rejected"
+    };
+    let data = if self.event.settlement_rule == SettlementRule::SingleCheckIn {
+      result.to_vec()
+    } else {
+      [
+        &self.attendee_record.sessions_attended.to_le_bytes()[..],
+        result,
+      ]
+      .concat()
+    };
+    write_ticket_app_data(
+      &TicketCpiAccounts {
+        mpl_core_program: &self.mpl_core_program.to_account_info(),
+        ticket: &self.ticket.to_account_info(),
+        event_collection: &self.event_collection.to_account_info(),
+        payer: &self.authority.to_account_info(),
+        community: &self.community,
+        system_program: &self.system_program.to_account_info(),
+      },
+      data,
+    )
+  }
+}
+
+pub fn amend_attendance_handler(
+  ctx: Context<AmendAttendance>,
+  status: AttendeeStatus,
+) -> Result<()> {
+  let event = &ctx.accounts.event;
+
+  event.assert_amendable(Clock::get()?.unix_timestamp)?;
+  event.assert_role(
+    &ctx.accounts.authority.key(),
+    &ctx.accounts.community.authority,
+    ROLE_APPROVER,
+  )?;
+
+  ctx.accounts.attendee_record.amend(
+    status.clone(),
+    event,
+    &mut ctx.accounts.vault,
+    &mut ctx.accounts.attendance_history,
+  )?;
+
+  ctx.accounts.rewrite_ticket_scan(&status)
+}
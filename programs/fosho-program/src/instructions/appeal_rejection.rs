@@ -0,0 +1,204 @@
+use crate::{
+  constant::*,
+  error::FoshoErrors,
+  state::*,
+  utils::{write_ticket_app_data, TicketCpiAccounts},
+};
+use anchor_lang::prelude::*;
+
+use mpl_core::{
+  accounts::{BaseAssetV1, BaseCollectionV1},
+  types::UpdateAuthority,
+  ID as MPL_CORE_ID,
+};
+
+#[derive(Accounts)]
+pub struct AppealRejection<'info> {
+  #[account(
+    init,
+    seeds = [
+      APPEAL_PRE_SEED,
+      attendee_record.key().as_ref(),
+    ],
+    bump,
+    payer = owner,
+    space = 8 + Appeal::INIT_SPACE
+  )]
+  pub appeal: Box<Account<'info, Appeal>>,
+  #[account(
+    seeds = [
+      ATTENDEE_PRE_SEED,
+      event.key().as_ref(),
+      owner.key().as_ref()
+    ],
+    bump = attendee_record.bump,
+    has_one = event,
+    has_one = owner,
+  )]
+  pub attendee_record: Box<Account<'info, Attendee>>,
+  pub event: Box<Account<'info, Event>>,
+  #[account(mut)]
+  pub owner: Signer<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveAppeal<'info> {
+  #[account(
+    mut,
+    seeds = [
+      APPEAL_PRE_SEED,
+      attendee_record.key().as_ref(),
+    ],
+    bump = appeal.bump,
+    has_one = attendee_record,
+  )]
+  pub appeal: Box<Account<'info, Appeal>>,
+  #[account(
+    mut,
+    seeds = [
+      ATTENDEE_PRE_SEED,
+      event.key().as_ref(),
+      attendee_record.owner.key().as_ref()
+    ],
+    bump = attendee_record.bump,
+    has_one = event,
+  )]
+  pub attendee_record: Box<Account<'info, Attendee>>,
+  #[account(
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
+  #[account(
+    mut,
+    seeds = [
+      ATTENDANCE_PRE_SEED,
+      community.key().as_ref(),
+      attendee_record.owner.as_ref()
+    ],
+    bump = attendance_history.bump,
+  )]
+  pub attendance_history: Box<Account<'info, AttendanceHistory>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED,
+        event.key().as_ref(),
+        EVENT_COLLECTION_SUFFIX_SEED,
+      ],
+      bump,
+      constraint = event_collection.update_authority == community.key(),
+  )]
+  pub event_collection: Box<Account<'info, BaseCollectionV1>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED,
+        event.key().as_ref(),
+        attendee_record.owner.key().as_ref(),
+        TICKET_SUFFIX_SEED,
+      ],
+      bump,
+      constraint = ticket.update_authority == UpdateAuthority::Collection(event_collection.key()),
+  )]
+  pub ticket: Box<Account<'info, BaseAssetV1>>,
+  /// community authority or an approver of the event
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+  /// CHECK: This is checked by the address constraint
+  #[account(address = MPL_CORE_ID)]
+  pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+/// rejected attendees can appeal as long as the rejection can be amended
+pub fn appeal_rejection_handler(ctx: Context<AppealRejection>, reason: String) -> Result<()> {
+  let current_time = Clock::get()?.unix_timestamp;
+
+  ctx.accounts.event.assert_amendable(current_time)?;
+  require!(
+    ctx.accounts.attendee_record.status == AttendeeStatus::Rejected,
+    FoshoErrors::NotRejected
+  );
+  require!(
+    reason.len() <= MAX_APPEAL_REASON_LEN,
+    FoshoErrors::AppealReasonTooLong
+  );
+
+  let appeal = &mut ctx.accounts.appeal;
+  appeal.attendee_record = ctx.accounts.attendee_record.key();
+  appeal.event = ctx.accounts.event.key();
+  appeal.owner = ctx.accounts.owner.key();
+  appeal.bump = ctx.bumps.appeal;
+  appeal.status = AppealStatus::Pending;
+  appeal.created_at = current_time;
+  appeal.reason = reason;
+
+  Ok(())
+}
+
+/// an approved appeal amends the attendee to Verified
+pub fn resolve_appeal_handler(ctx: Context<ResolveAppeal>, approve: bool) -> Result<()> {
+  let event = &ctx.accounts.event;
+
+  event.assert_amendable(Clock::get()?.unix_timestamp)?;
+  event.assert_role(
+    &ctx.accounts.authority.key(),
+    &ctx.accounts.community.authority,
+    ROLE_APPROVER,
+  )?;
+  require!(
+    ctx.accounts.appeal.status == AppealStatus::Pending,
+    FoshoErrors::AppealAlreadyResolved
+  );
+
+  if !approve {
+    ctx.accounts.appeal.status = AppealStatus::Denied;
+    return Ok(());
+  }
+
+  ctx.accounts.attendee_record.amend(
+    AttendeeStatus::Verified,
+    event,
+    &mut ctx.accounts.vault,
+    &mut ctx.accounts.attendance_history,
+  )?;
+  ctx.accounts.appeal.status = AppealStatus::Approved;
+
+  write_ticket_app_data(
+    &TicketCpiAccounts {
+      mpl_core_program: &ctx.accounts.mpl_core_program.to_account_info(),
+      ticket: &ctx.accounts.ticket.to_account_info(),
+      event_collection: &ctx.accounts.event_collection.to_account_info(),
+      payer: &ctx.accounts.authority.to_account_info(),
+      community: &ctx.accounts.community,
+      system_program: &ctx.accounts.system_program.to_account_info(),
+    },
+    b"Verified".to_vec(),
+  )
+}
@@ -0,0 +1,364 @@
+use crate::{
+  constant::*,
+  error::FoshoErrors,
+  state::*,
+  utils::{check_if_already_scanned, write_ticket_scan, TicketCpiAccounts},
+};
+use anchor_lang::{
+  prelude::*,
+  system_program::{self, allocate, assign, transfer, Allocate, Assign, Transfer},
+  Discriminator,
+};
+
+use mpl_core::{
+  accounts::{BaseAssetV1, BaseCollectionV1},
+  types::UpdateAuthority,
+  ID as MPL_CORE_ID,
+};
+
+/// Why an entry of a batch scan was left untouched.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum ScanSkipReason {
+  /// the accounts do not belong to this event or to the same attendee
+  InvalidAccounts,
+  AlreadyVerified,
+  AlreadyRejected,
+  AlreadyClaimed,
+  /// the ticket already carries a scan result
+  TicketAlreadyScanned,
+  AlreadyCheckedOut,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct SkippedScan {
+  /// position of the entry in the remaining accounts
+  pub index: u16,
+  pub reason: ScanSkipReason,
+}
+
+/// Remaining accounts, one group per attendee:
+/// - verify_attendees: attendee_record, ticket, owner, attendance_history (created if missing)
+/// - reject_attendees: attendee_record, ticket, owner
+#[derive(Accounts)]
+pub struct BatchScanAttendees<'info> {
+  #[account(
+    seeds = [
+      EVENT_PRE_SEED.as_ref(),
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED.as_ref(),
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED.as_ref(),
+        event.key().as_ref(),
+        EVENT_COLLECTION_SUFFIX_SEED.as_ref(),
+      ],
+      bump,
+      constraint = event_collection.update_authority == community.key(),
+  )]
+  pub event_collection: Box<Account<'info, BaseCollectionV1>>,
+  #[account(mut)]
+  pub event_authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+  /// CHECK: This is checked by the address constraint
+  #[account(address = MPL_CORE_ID)]
+  pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+/// Accounts of one batch entry that passed every check.
+struct ScanEntry<'info> {
+  attendee_record: Account<'info, Attendee>,
+  ticket: &'info AccountInfo<'info>,
+}
+
+impl<'info> BatchScanAttendees<'info> {
+  /// same checks as the accounts constraints of verify_attendee and reject_attendee
+  fn load_entry(
+    &self,
+    attendee_record: &'info AccountInfo<'info>,
+    ticket: &'info AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+  ) -> std::result::Result<ScanEntry<'info>, ScanSkipReason> {
+    if !attendee_record.is_writable || !ticket.is_writable {
+      return Err(ScanSkipReason::InvalidAccounts);
+    }
+    let attendee_record = Account::<Attendee>::try_from(attendee_record)
+      .map_err(|_| ScanSkipReason::InvalidAccounts)?;
+    if attendee_record.event != self.event.key() || attendee_record.owner != owner.key() {
+      return Err(ScanSkipReason::InvalidAccounts);
+    }
+
+    match attendee_record.status {
+      AttendeeStatus::Pending => {}
+      AttendeeStatus::Verified => return Err(ScanSkipReason::AlreadyVerified),
+      AttendeeStatus::Rejected => return Err(ScanSkipReason::AlreadyRejected),
+      AttendeeStatus::Claimed => return Err(ScanSkipReason::AlreadyClaimed),
+      AttendeeStatus::PartiallyAttended => return Err(ScanSkipReason::AlreadyCheckedOut),
+    }
+
+    let event_key = self.event.key();
+    let (ticket_key, _) = Pubkey::find_program_address(
+      &[
+        EVENT_PRE_SEED,
+        event_key.as_ref(),
+        owner.key.as_ref(),
+        TICKET_SUFFIX_SEED,
+      ],
+      &crate::ID,
+    );
+    if ticket.key() != ticket_key || *ticket.owner != MPL_CORE_ID {
+      return Err(ScanSkipReason::InvalidAccounts);
+    }
+    let asset = ticket
+      .try_borrow_data()
+      .ok()
+      .and_then(|data| BaseAssetV1::from_bytes(&data).ok())
+      .ok_or(ScanSkipReason::InvalidAccounts)?;
+    if asset.owner != owner.key()
+      || asset.update_authority != UpdateAuthority::Collection(self.event_collection.key())
+    {
+      return Err(ScanSkipReason::InvalidAccounts);
+    }
+
+    check_if_already_scanned(ticket.clone(), &self.community.key())
+      .map_err(|_| ScanSkipReason::TicketAlreadyScanned)?;
+
+    Ok(ScanEntry {
+      attendee_record,
+      ticket,
+    })
+  }
+
+  /// same as the init_if_needed attendance history of verify_attendee
+  fn load_attendance_history(
+    &self,
+    attendance_history: &'info AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+  ) -> Result<Option<Account<'info, AttendanceHistory>>> {
+    let community_key = self.community.key();
+    let (history_key, bump) = Pubkey::find_program_address(
+      &[
+        ATTENDANCE_PRE_SEED,
+        community_key.as_ref(),
+        owner.key.as_ref(),
+      ],
+      &crate::ID,
+    );
+    if attendance_history.key() != history_key || !attendance_history.is_writable {
+      return Ok(None);
+    }
+    if *attendance_history.owner == system_program::ID {
+      let signer_seeds: &[&[u8]] = &[
+        ATTENDANCE_PRE_SEED,
+        community_key.as_ref(),
+        owner.key.as_ref(),
+        &[bump],
+      ];
+      self.create_attendance_history(attendance_history, signer_seeds)?;
+    }
+
+    let Ok(mut history) = Account::<AttendanceHistory>::try_from(attendance_history) else {
+      return Ok(None);
+    };
+    history.init_if_new(community_key, owner.key(), bump);
+    Ok(Some(history))
+  }
+
+  /// accounts funded beforehand are topped up, allocated and assigned instead
+  fn create_attendance_history(
+    &self,
+    attendance_history: &AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+  ) -> Result<()> {
+    let space = 8 + AttendanceHistory::INIT_SPACE;
+    let shortfall = Rent::get()?
+      .minimum_balance(space)
+      .saturating_sub(attendance_history.lamports());
+    let system_program = self.system_program.to_account_info();
+    if shortfall.gt(&0) {
+      let cpi_accounts = Transfer {
+        from: self.event_authority.to_account_info(),
+        to: attendance_history.clone(),
+      };
+      transfer(
+        CpiContext::new(system_program.clone(), cpi_accounts),
+        shortfall,
+      )?;
+    }
+    allocate(
+      CpiContext::new_with_signer(
+        system_program.clone(),
+        Allocate {
+          account_to_allocate: attendance_history.clone(),
+        },
+        &[signer_seeds],
+      ),
+      space as u64,
+    )?;
+    assign(
+      CpiContext::new_with_signer(
+        system_program,
+        Assign {
+          account_to_assign: attendance_history.clone(),
+        },
+        &[signer_seeds],
+      ),
+      &crate::ID,
+    )?;
+    attendance_history.try_borrow_mut_data()?[..8]
+      .copy_from_slice(&AttendanceHistory::DISCRIMINATOR);
+    Ok(())
+  }
+
+  fn scan_ticket(&self, ticket: &AccountInfo<'info>, result: &str) -> Result<()> {
+    write_ticket_scan(
+      &TicketCpiAccounts {
+        mpl_core_program: &self.mpl_core_program.to_account_info(),
+        ticket,
+        event_collection: &self.event_collection.to_account_info(),
+        payer: &self.event_authority.to_account_info(),
+        community: &self.community,
+        system_program: &self.system_program.to_account_info(),
+      },
+      result,
+    )
+  }
+
+  fn assert_scan_allowed(&self, action: EventAction, role: u8) -> Result<()> {
+    self.event.assert_allows(action)?;
+    self
+      .event
+      .assert_role(&self.event_authority.key(), &self.community.authority, role)
+  }
+}
+
+fn skip(skipped: &mut Vec<SkippedScan>, index: usize, reason: ScanSkipReason) {
+  msg!("Skipped entry {}: {:?}", index, reason);
+  skipped.push(SkippedScan {
+    index: index as u16,
+    reason,
+  });
+}
+
+pub fn verify_attendees_handler<'info>(
+  ctx: Context<'_, '_, 'info, 'info, BatchScanAttendees<'info>>,
+) -> Result<Vec<SkippedScan>> {
+  let accounts = &mut *ctx.accounts;
+  accounts.assert_scan_allowed(EventAction::Verify, ROLE_SCANNER)?;
+  accounts.event.settlement_rule.assert_single_check_in()?;
+  // the owners cannot sign a check-in challenge in a batch
+  require!(
+    accounts.event.check_in_mode == CheckInMode::Staff,
+    FoshoErrors::ProofOfPresenceRequired
+  );
+  let current_time = Clock::get()?.unix_timestamp;
+  accounts.event.schedule.assert_in_progress(current_time)?;
+  let result = if accounts.event.check_out_policy.is_required() {
+    "Checked In"
+  } else {
+    "Verified"
+  };
+
+  let entries = ctx.remaining_accounts.chunks_exact(4);
+  require!(
+    entries.remainder().is_empty(),
+    FoshoErrors::NotEnoughRemainingAccounts
+  );
+
+  let mut skipped = Vec::new();
+  for (index, entry) in entries.enumerate() {
+    let [attendee_record, ticket, owner, attendance_history]: &[AccountInfo; 4] = entry
+      .try_into()
+      .map_err(|_| FoshoErrors::NotEnoughRemainingAccounts)?;
+    let ScanEntry {
+      mut attendee_record,
+      ticket,
+    } = match accounts.load_entry(attendee_record, ticket, owner) {
+      Ok(scan_entry) => scan_entry,
+      Err(reason) => {
+        skip(&mut skipped, index, reason);
+        continue;
+      }
+    };
+    let Some(mut attendance_history) =
+      accounts.load_attendance_history(attendance_history, owner)?
+    else {
+      skip(&mut skipped, index, ScanSkipReason::InvalidAccounts);
+      continue;
+    };
+
+    attendee_record.check_in(
+      &accounts.event,
+      &mut accounts.vault,
+      &mut attendance_history,
+      current_time,
+    )?;
+
+    accounts.scan_ticket(ticket, result)?;
+    attendee_record.exit(&crate::ID)?;
+    attendance_history.exit(&crate::ID)?;
+  }
+
+  Ok(skipped)
+}
+
+pub fn reject_attendees_handler<'info>(
+  ctx: Context<'_, '_, 'info, 'info, BatchScanAttendees<'info>>,
+) -> Result<Vec<SkippedScan>> {
+  let accounts = &ctx.accounts;
+  accounts.assert_scan_allowed(EventAction::Reject, ROLE_APPROVER)?;
+
+  let entries = ctx.remaining_accounts.chunks_exact(3);
+  require!(
+    entries.remainder().is_empty(),
+    FoshoErrors::NotEnoughRemainingAccounts
+  );
+
+  let mut skipped = Vec::new();
+  for (index, entry) in entries.enumerate() {
+    let [attendee_record, ticket, owner]: &[AccountInfo; 3] = entry
+      .try_into()
+      .map_err(|_| FoshoErrors::NotEnoughRemainingAccounts)?;
+    let ScanEntry {
+      mut attendee_record,
+      ticket,
+    } = match accounts.load_entry(attendee_record, ticket, owner) {
+      Ok(scan_entry) => scan_entry,
+      Err(reason) => {
+        skip(&mut skipped, index, reason);
+        continue;
+      }
+    };
+
+    attendee_record.status = AttendeeStatus::Rejected;
+
+    accounts.scan_ticket(ticket, "Rejected")?;
+    attendee_record.exit(&crate::ID)?;
+  }
+
+  Ok(skipped)
+}
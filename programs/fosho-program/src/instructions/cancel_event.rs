@@ -20,15 +20,28 @@ pub struct CancelEvent<'info> {
       community.seed.as_ref(),
     ],
     bump = community.bump,
-    has_one = authority
   )]
   pub community: Account<'info, Community>,
+  /// required once the governance of the community is enabled
+  #[account(mut, has_one = community)]
+  pub proposal: Option<Box<Account<'info, Proposal>>>,
+  /// community authority, or anyone executing an approved proposal
   pub authority: Signer<'info>,
 }
 
 pub fn cancel_event_handler(ctx: Context<CancelEvent>) -> Result<()> {
+  ctx.accounts.community.authorize(
+    &ctx.accounts.authority.key(),
+    ctx.accounts.proposal.as_deref_mut(),
+    ProposalAction::CancelEvent {
+      event: ctx.accounts.event.key(),
+    },
+  )?;
+
   let event = &mut ctx.accounts.event;
-  event.is_cancelled = true;
+  event.transition_to(EventStatus::Cancelled)?;
+  // attendees can claim their commitment fee back from here on
+  event.settled_at = Some(Clock::get()?.unix_timestamp);
   // should we burn the collection here?
   // can an event be cancelled after join event occured?
   Ok(())
@@ -0,0 +1,159 @@
+use crate::{
+  constant::*,
+  error::FoshoErrors,
+  state::*,
+  utils::{write_ticket_app_data, TicketCpiAccounts},
+};
+use anchor_lang::prelude::*;
+
+use mpl_core::{
+  accounts::{BaseAssetV1, BaseCollectionV1},
+  types::UpdateAuthority,
+  ID as MPL_CORE_ID,
+};
+
+#[derive(Accounts)]
+pub struct CheckOutAttendee<'info> {
+  #[account(
+    mut,
+    seeds = [
+      ATTENDEE_PRE_SEED,
+      event.key().as_ref(),
+      attendee_record.owner.key().as_ref()
+    ],
+    bump = attendee_record.bump,
+    has_one = event,
+  )]
+  pub attendee_record: Box<Account<'info, Attendee>>,
+  #[account(
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
+  #[account(
+    mut,
+    seeds = [
+      ATTENDANCE_PRE_SEED,
+      community.key().as_ref(),
+      attendee_record.owner.as_ref()
+    ],
+    bump = attendance_history.bump,
+  )]
+  pub attendance_history: Box<Account<'info, AttendanceHistory>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED,
+        event.key().as_ref(),
+        EVENT_COLLECTION_SUFFIX_SEED,
+      ],
+      bump,
+      constraint = event_collection.update_authority == community.key(),
+  )]
+  pub event_collection: Box<Account<'info, BaseCollectionV1>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED,
+        event.key().as_ref(),
+        attendee_record.owner.key().as_ref(),
+        TICKET_SUFFIX_SEED,
+      ],
+      bump,
+      constraint = ticket.owner == owner.key(),
+      constraint = ticket.update_authority == UpdateAuthority::Collection(event_collection.key()),
+  )]
+  pub ticket: Box<Account<'info, BaseAssetV1>>,
+  pub system_program: Program<'info, System>,
+  /// CHECK: This is checked by the ticket constraint
+  pub owner: AccountInfo<'info>,
+  #[account(mut)]
+  pub event_authority: Signer<'info>,
+  /// CHECK: This is checked by the address constraint
+  #[account(address = MPL_CORE_ID)]
+  pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+impl<'info> CheckOutAttendee<'info> {
+  /// the ticket was frozen on check-in, only its app data changes
+  pub fn scan_ticket(&self, result: &str) -> Result<()> {
+    write_ticket_app_data(
+      &TicketCpiAccounts {
+        mpl_core_program: &self.mpl_core_program.to_account_info(),
+        ticket: &self.ticket.to_account_info(),
+        event_collection: &self.event_collection.to_account_info(),
+        payer: &self.event_authority.to_account_info(),
+        community: &self.community,
+        system_program: &self.system_program.to_account_info(),
+      },
+      result.as_bytes().to_vec(),
+    )
+  }
+}
+
+pub fn check_out_attendee_handler(ctx: Context<CheckOutAttendee>) -> Result<()> {
+  let attendee_record = &mut ctx.accounts.attendee_record;
+  let event = &ctx.accounts.event;
+
+  event.assert_allows(EventAction::Verify)?;
+  event.assert_role(
+    &ctx.accounts.event_authority.key(),
+    &ctx.accounts.community.authority,
+    ROLE_SCANNER,
+  )?;
+
+  match attendee_record.status {
+    AttendeeStatus::Pending => {}
+    AttendeeStatus::Claimed => {
+      return Err(FoshoErrors::AlreadyClaimed.into());
+    }
+    AttendeeStatus::Rejected | AttendeeStatus::Verified | AttendeeStatus::PartiallyAttended => {
+      return Err(FoshoErrors::AlreadyScanned.into());
+    }
+  }
+  let checked_in_at = attendee_record
+    .checked_in_at
+    .ok_or(FoshoErrors::NotCheckedIn)?;
+
+  let current_time = Clock::get()?.unix_timestamp;
+  let stayed = event.check_out_policy.is_met(checked_in_at, current_time)?;
+  attendee_record.checked_out_at = Some(current_time);
+
+  if stayed {
+    // the full fee is owed instead of the partial refund
+    let partial_refund = event
+      .check_out_policy
+      .partial_refund(attendee_record.commitment_fee)?;
+    let vault = &mut ctx.accounts.vault;
+    vault.partial_refunds = vault.partial_refunds.checked_sub(partial_refund).unwrap();
+    attendee_record.verify(vault, &mut ctx.accounts.attendance_history, event.nonce)?;
+    ctx.accounts.scan_ticket("Verified")
+  } else {
+    attendee_record.status = AttendeeStatus::PartiallyAttended;
+    ctx.accounts.scan_ticket("Partially Attended")
+  }
+}
@@ -1,14 +1,15 @@
-use crate::{constant::*, error::FoshoErrors, state::*, utils::get_event_ends_at_from_attributes};
-use anchor_lang::prelude::*;
+use crate::{
+  constant::*,
+  error::FoshoErrors,
+  state::*,
+  utils::{burn_from_vault, transfer_from_event, transfer_from_vault},
+};
+use anchor_lang::{prelude::*, solana_program::incinerator};
 use anchor_spl::{
   associated_token::AssociatedToken,
-  token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
-};
-use mpl_core::{
-  accounts::BaseCollectionV1,
-  fetch_plugin,
-  types::{Attributes, PluginType},
+  token_interface::{Mint, TokenAccount, TokenInterface},
 };
+use mpl_core::accounts::BaseCollectionV1;
 
 #[derive(Accounts)]
 pub struct ClaimRewards<'info> {
@@ -32,6 +33,17 @@ pub struct ClaimRewards<'info> {
     bump = event.bump,
   )]
   pub event: Box<Account<'info, Event>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
   #[account(
     seeds = [
       COMMUNITY_PRE_SEED.as_ref(),
@@ -40,6 +52,9 @@ pub struct ClaimRewards<'info> {
     bump = community.bump
   )]
   pub community: Account<'info, Community>,
+  /// required for the claims of the community authority once the governance is enabled
+  #[account(mut, has_one = community)]
+  pub proposal: Option<Box<Account<'info, Proposal>>>,
   #[account(
     mint::token_program = token_program,
   )]
@@ -73,26 +88,160 @@ pub struct ClaimRewards<'info> {
   pub claimer: Signer<'info>,
   pub token_program: Interface<'info, TokenInterface>,
   pub associated_token_program: Program<'info, AssociatedToken>,
+  /// the fee accounts are required if the commitment fee is paid in tokens
+  #[account(
+    mut,
+    mint::token_program = fee_token_program,
+  )]
+  pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = vault,
+    associated_token::token_program = fee_token_program
+  )]
+  pub fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = claimer,
+    associated_token::token_program = fee_token_program
+  )]
+  pub fee_receiver_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  /// treasury of the Treasury forfeit policy, or the incinerator to burn SOL fees
+  /// CHECK: checked against the forfeit policy of the event
+  #[account(mut)]
+  pub forfeit_receiver: Option<UncheckedAccount<'info>>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = forfeit_receiver,
+    associated_token::token_program = fee_token_program
+  )]
+  pub forfeit_receiver_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  pub fee_token_program: Option<Interface<'info, TokenInterface>>,
 }
 
 impl<'info> ClaimRewards<'info> {
-  pub fn claim_reward_tokens(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
-    let cpi_accounts = TransferChecked {
-      from: self.reward_account.as_ref().unwrap().to_account_info(),
-      to: self.receiver_account.as_ref().unwrap().to_account_info(),
-      mint: self.reward_mint.as_ref().unwrap().to_account_info(),
-      authority: self.event.to_account_info(),
-    };
+  pub fn claim_reward_tokens(&self, reward_amount: u64) -> Result<()> {
+    // the reward account is owned by the event
+    transfer_from_event(
+      &self.event,
+      self.reward_account.as_ref().unwrap().to_account_info(),
+      self.receiver_account.as_ref().unwrap().to_account_info(),
+      self.reward_mint.as_ref().unwrap(),
+      self.token_program.to_account_info(),
+      reward_amount,
+    )
+  }
 
-    let cpi_program = self.token_program.to_account_info();
+  /// pays out of the vault, in SOL or in the fee token of the event
+  fn pay_commitment_fee(
+    &self,
+    receiver: AccountInfo<'info>,
+    receiver_fee_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    amount: u64,
+  ) -> Result<()> {
+    if self.event.fee_mint.is_none() {
+      self.vault.sub_lamports(amount)?;
+      receiver.add_lamports(amount)?;
+      return Ok(());
+    }
 
-    CpiContext::new(cpi_program, cpi_accounts)
+    if self.fee_mint.is_none()
+      || self.fee_account.is_none()
+      || receiver_fee_account.is_none()
+      || self.fee_token_program.is_none()
+    {
+      return Err(FoshoErrors::AccountNotProvided.into());
+    }
+    let fee_mint = self.fee_mint.as_ref().unwrap();
+    require!(
+      self.event.fee_mint == Some(fee_mint.key()),
+      FoshoErrors::PublicKeyMismatch
+    );
+
+    transfer_from_vault(
+      &self.vault,
+      self.fee_account.as_ref().unwrap().to_account_info(),
+      receiver_fee_account.unwrap().to_account_info(),
+      fee_mint,
+      self.fee_token_program.as_ref().unwrap().to_account_info(),
+      amount,
+    )
   }
 
   pub fn claim_commitment_fee(&self, commitment_fee: u64) -> Result<()> {
-    self.event.sub_lamports(commitment_fee)?;
-    self.claimer.add_lamports(commitment_fee)?;
-    Ok(())
+    self.pay_commitment_fee(
+      self.claimer.to_account_info(),
+      self.fee_receiver_account.as_deref(),
+      commitment_fee,
+    )
+  }
+
+  fn pay_forfeit_receiver(&self, expected: Pubkey, commitment_fee: u64) -> Result<()> {
+    let forfeit_receiver = self
+      .forfeit_receiver
+      .as_ref()
+      .ok_or(FoshoErrors::AccountNotProvided)?;
+    require_keys_eq!(
+      forfeit_receiver.key(),
+      expected,
+      FoshoErrors::InvalidForfeitReceiver
+    );
+
+    self.pay_commitment_fee(
+      forfeit_receiver.to_account_info(),
+      self.forfeit_receiver_fee_account.as_deref(),
+      commitment_fee,
+    )
+  }
+
+  fn burn_commitment_fee(&self, commitment_fee: u64) -> Result<()> {
+    if self.fee_mint.is_none() || self.fee_account.is_none() || self.fee_token_program.is_none() {
+      return Err(FoshoErrors::AccountNotProvided.into());
+    }
+    let fee_mint = self.fee_mint.as_ref().unwrap();
+    require!(
+      self.event.fee_mint == Some(fee_mint.key()),
+      FoshoErrors::PublicKeyMismatch
+    );
+
+    burn_from_vault(
+      &self.vault,
+      self.fee_account.as_ref().unwrap().to_account_info(),
+      fee_mint,
+      self.fee_token_program.as_ref().unwrap().to_account_info(),
+      commitment_fee,
+    )
+  }
+
+  /// applies the forfeit policy of the event to the fee of a no-show or rejected attendee
+  pub fn forfeit_commitment_fee(&mut self, commitment_fee: u64) -> Result<()> {
+    match self.event.forfeit_policy.clone() {
+      // stays in the vault, verified attendees claim it as a bonus
+      ForfeitPolicy::ProRata if self.vault.verified_fees.gt(&0) => return Ok(()),
+      ForfeitPolicy::ToOrganizer | ForfeitPolicy::ProRata => {
+        if self.claimer.key() == self.community.authority {
+          self.claim_commitment_fee(commitment_fee)?;
+        } else {
+          // remainder of a partially attended claim
+          self.pay_forfeit_receiver(self.community.authority, commitment_fee)?;
+        }
+      }
+      ForfeitPolicy::Treasury { address } => {
+        self.pay_forfeit_receiver(address, commitment_fee)?;
+      }
+      ForfeitPolicy::Burn => {
+        if self.event.fee_mint.is_some() {
+          self.burn_commitment_fee(commitment_fee)?;
+        } else {
+          self.pay_forfeit_receiver(incinerator::ID, commitment_fee)?;
+        }
+      }
+    }
+
+    self.vault.record_forfeit(commitment_fee)
   }
 }
 
@@ -102,51 +251,65 @@ pub fn claim_rewards_handler(ctx: Context<ClaimRewards>) -> Result<()> {
   let community = &ctx.accounts.community;
   let event = &ctx.accounts.event;
 
+  event.assert_allows(EventAction::Claim)?;
+
   // if event has been cancelled we assume the attendee attended the event
   // thus, they should be able to claim the reward/commitment_fee
   // by which the community authority has decided to cancel the event.
-  if event.is_cancelled {
-    attendee_record.status = AttendeeStatus::Verified;
+  // claimed records keep their status so that they cannot be claimed twice.
+  if event.status == EventStatus::Cancelled {
+    if attendee_record.status != AttendeeStatus::Claimed {
+      attendee_record.status = AttendeeStatus::Verified;
+    }
+  } else if attendee_record.left_without_check_out() {
+    attendee_record.status = AttendeeStatus::PartiallyAttended;
   }
 
   match attendee_record.status {
     AttendeeStatus::Pending => {
       // community_authority has to be able to claim the reward/commitement_fee if the attendee did not attend the event
-      // if and only if the event ended.
+      // claims are only possible once the event is settled.
       if claimer != community.authority {
         return Err(FoshoErrors::AttendeeStatusPending.into());
       }
-      let (_, collection_attribute_list, _) = fetch_plugin::<BaseCollectionV1, Attributes>(
-        &ctx.accounts.event_collection.to_account_info(),
-        PluginType::Attributes,
+      community.authorize(
+        &claimer,
+        ctx.accounts.proposal.as_deref_mut(),
+        ProposalAction::ClaimForfeits { event: event.key() },
       )?;
-      let event_ends_at =
-        get_event_ends_at_from_attributes(&collection_attribute_list.attribute_list)?;
-
-      let current_unix_ts = Clock::get()?.unix_timestamp as u64;
-      if event_ends_at.ne(&0) {
-        require!(
-          current_unix_ts <= event_ends_at,
-          FoshoErrors::EventHasNotEnded
-        );
-      }
     }
     AttendeeStatus::Claimed => {
       return Err(FoshoErrors::AlreadyClaimed.into());
     }
     AttendeeStatus::Rejected => {
       require_keys_eq!(claimer, community.authority, FoshoErrors::InvalidClaimer);
+      community.authorize(
+        &claimer,
+        ctx.accounts.proposal.as_deref_mut(),
+        ProposalAction::ClaimForfeits { event: event.key() },
+      )?;
     }
-    AttendeeStatus::Verified => {
+    AttendeeStatus::Verified | AttendeeStatus::PartiallyAttended => {
       require_keys_eq!(claimer, attendee_record.owner, FoshoErrors::InvalidClaimer);
     }
   }
 
+  let is_attendee_claim = attendee_record.status == AttendeeStatus::Verified;
+  let is_partial_claim = attendee_record.status == AttendeeStatus::PartiallyAttended;
   attendee_record.status = AttendeeStatus::Claimed;
+  ctx.accounts.event.claimed_count = ctx.accounts.event.claimed_count.checked_add(1).unwrap();
 
-  if event.reward_per_user.gt(&0) {
-    let reward_mint = ctx.accounts.reward_mint.as_ref();
-
+  // amounts are the ones of the ticket tier the attendee joined with
+  let reward_amount = ctx.accounts.attendee_record.reward_amount;
+  if reward_amount.gt(&0) && is_partial_claim {
+    // the reward is only earned by staying, it becomes unallocated
+    ctx.accounts.event.reward_liability = ctx
+      .accounts
+      .event
+      .reward_liability
+      .checked_sub(reward_amount)
+      .unwrap();
+  } else if reward_amount.gt(&0) {
     if ctx.accounts.reward_mint.is_none()
       || ctx.accounts.reward_account.is_none()
       || ctx.accounts.receiver_account.is_none()
@@ -154,15 +317,41 @@ pub fn claim_rewards_handler(ctx: Context<ClaimRewards>) -> Result<()> {
       return Err(FoshoErrors::AccountNotProvided.into());
     }
 
-    transfer_checked(
-      ctx.accounts.claim_reward_tokens(),
-      event.reward_per_user,
-      reward_mint.unwrap().decimals,
-    )?;
+    ctx.accounts.claim_reward_tokens(reward_amount)?;
+    ctx.accounts.event.reward_liability = ctx
+      .accounts
+      .event
+      .reward_liability
+      .checked_sub(reward_amount)
+      .unwrap();
   }
 
-  if event.commitment_fee.gt(&0) {
-    ctx.accounts.claim_commitment_fee(event.commitment_fee)?;
+  let commitment_fee = ctx.accounts.attendee_record.commitment_fee;
+  if is_attendee_claim {
+    // the forfeit pool is only set on settlement with the pro-rata forfeit policy
+    let bonus = ctx.accounts.vault.forfeit_share(commitment_fee)?;
+    let payout = commitment_fee.checked_add(bonus).unwrap();
+    if payout.gt(&0) {
+      ctx.accounts.claim_commitment_fee(payout)?;
+    }
+    ctx.accounts.vault.record_refund(commitment_fee)?;
+    ctx.accounts.vault.record_forfeit(bonus)?;
+  } else if is_partial_claim {
+    let refund = ctx
+      .accounts
+      .event
+      .check_out_policy
+      .partial_refund(commitment_fee)?;
+    if refund.gt(&0) {
+      ctx.accounts.claim_commitment_fee(refund)?;
+    }
+    ctx.accounts.vault.record_refund(refund)?;
+    let forfeit = commitment_fee.checked_sub(refund).unwrap();
+    if forfeit.gt(&0) {
+      ctx.accounts.forfeit_commitment_fee(forfeit)?;
+    }
+  } else if commitment_fee.gt(&0) {
+    ctx.accounts.forfeit_commitment_fee(commitment_fee)?;
   }
 
   Ok(())
@@ -0,0 +1,380 @@
+use crate::{
+  constant::*,
+  error::FoshoErrors,
+  state::*,
+  utils::{
+    assert_keys_equal, burn_from_vault, close_program_account, transfer_from_event,
+    transfer_from_vault,
+  },
+};
+use anchor_lang::{prelude::*, solana_program::incinerator, Discriminator};
+use anchor_spl::{
+  token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeAmount, BaseStateWithExtensions, StateWithExtensions},
+    state::Account as TokenAccountState,
+  },
+  token_interface::{
+    close_account, harvest_withheld_tokens_to_mint, CloseAccount, HarvestWithheldTokensToMint,
+    Mint, TokenAccount, TokenInterface,
+  },
+};
+
+#[derive(Accounts)]
+pub struct CloseEvent<'info> {
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+    close = authority,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  /// unclaimed SOL fees follow the forfeit policy, the rent of the vault goes to the organizer
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+    close = authority,
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+    has_one = authority
+  )]
+  pub community: Box<Account<'info, Community>>,
+  #[account(
+    mint::token_program = token_program,
+  )]
+  pub reward_mint: Option<InterfaceAccount<'info, Mint>>,
+  #[account(
+    mut,
+    associated_token::mint = reward_mint,
+    associated_token::authority = event,
+    associated_token::token_program = token_program
+  )]
+  pub reward_account: Option<InterfaceAccount<'info, TokenAccount>>,
+  /// unused reward tokens are returned to the organizer
+  #[account(
+    mut,
+    associated_token::mint = reward_mint,
+    associated_token::authority = authority,
+    associated_token::token_program = token_program
+  )]
+  pub receiver_account: Option<InterfaceAccount<'info, TokenAccount>>,
+  /// receives the rent and the leftover tokens of the event
+  /// CHECK: checked by has_one on the community
+  #[account(mut)]
+  pub authority: UncheckedAccount<'info>,
+  /// required once the governance of the community is enabled
+  #[account(mut, has_one = community)]
+  pub proposal: Option<Box<Account<'info, Proposal>>>,
+  /// community authority, or anyone executing an approved proposal
+  pub executor: Signer<'info>,
+  pub token_program: Interface<'info, TokenInterface>,
+  /// forfeited and unclaimed fee tokens follow the forfeit policy,
+  /// withheld transfer fees of the vault are harvested to the mint
+  #[account(
+    mut,
+    mint::token_program = fee_token_program,
+  )]
+  pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = vault,
+    associated_token::token_program = fee_token_program
+  )]
+  pub fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = authority,
+    associated_token::token_program = fee_token_program
+  )]
+  pub fee_receiver_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  pub fee_token_program: Option<Interface<'info, TokenInterface>>,
+  /// treasury of the Treasury forfeit policy, or the incinerator to burn SOL fees
+  /// CHECK: checked against the forfeit policy of the event
+  #[account(mut)]
+  pub forfeit_receiver: Option<UncheckedAccount<'info>>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = forfeit_receiver,
+    associated_token::token_program = fee_token_program
+  )]
+  pub forfeit_receiver_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+impl<'info> CloseEvent<'info> {
+  pub fn sweep_reward_tokens(&self) -> Result<()> {
+    let reward_account = self.reward_account.as_ref().unwrap();
+
+    if reward_account.amount.gt(&0) {
+      transfer_from_event(
+        &self.event,
+        reward_account.to_account_info(),
+        self.receiver_account.as_ref().unwrap().to_account_info(),
+        self.reward_mint.as_ref().unwrap(),
+        self.token_program.to_account_info(),
+        reward_account.amount,
+      )?;
+    }
+
+    let community_binding = self.community.key();
+    let nonce_binding = self.event.nonce.to_le_bytes();
+    let event_seeds = &[
+      EVENT_PRE_SEED,
+      community_binding.as_ref(),
+      nonce_binding.as_ref(),
+      &[self.event.bump],
+    ];
+
+    let cpi_accounts = CloseAccount {
+      account: reward_account.to_account_info(),
+      destination: self.authority.to_account_info(),
+      authority: self.event.to_account_info(),
+    };
+    close_account(CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      cpi_accounts,
+      &[&event_seeds[..]],
+    ))
+  }
+
+  /// receiver of the unclaimed fees, None when they go to the organizer
+  fn forfeit_receiver(&self) -> Result<Option<&UncheckedAccount<'info>>> {
+    let expected = match self.event.forfeit_policy {
+      ForfeitPolicy::Treasury { address } => address,
+      ForfeitPolicy::Burn if self.event.fee_mint.is_none() => incinerator::ID,
+      _ => return Ok(None),
+    };
+    let forfeit_receiver = self
+      .forfeit_receiver
+      .as_ref()
+      .ok_or(FoshoErrors::AccountNotProvided)?;
+    require_keys_eq!(
+      forfeit_receiver.key(),
+      expected,
+      FoshoErrors::InvalidForfeitReceiver
+    );
+    Ok(Some(forfeit_receiver))
+  }
+
+  /// lamports of the vault above its rent are the SOL fees that were never claimed
+  pub fn sweep_unclaimed_lamports(&self) -> Result<()> {
+    let Some(forfeit_receiver) = self.forfeit_receiver()? else {
+      return Ok(());
+    };
+    let vault_info = self.vault.to_account_info();
+    let unclaimed = vault_info
+      .lamports()
+      .saturating_sub(Rent::get()?.minimum_balance(vault_info.data_len()));
+    if unclaimed.gt(&0) {
+      self.vault.sub_lamports(unclaimed)?;
+      forfeit_receiver.add_lamports(unclaimed)?;
+    }
+    Ok(())
+  }
+
+  /// fees left in the vault are the ones that were never claimed
+  pub fn sweep_fee_tokens(&self) -> Result<()> {
+    let fee_account = self.fee_account.as_ref().unwrap();
+    let fee_token_program = self.fee_token_program.as_ref().unwrap();
+
+    let event_binding = self.event.key();
+    let vault_seeds = &[
+      EVENT_PRE_SEED,
+      event_binding.as_ref(),
+      VAULT_SUFFIX_SEED,
+      &[self.vault.bump],
+    ];
+
+    if fee_account.amount.gt(&0) && self.event.forfeit_policy == ForfeitPolicy::Burn {
+      burn_from_vault(
+        &self.vault,
+        fee_account.to_account_info(),
+        self.fee_mint.as_ref().unwrap(),
+        fee_token_program.to_account_info(),
+        fee_account.amount,
+      )?;
+    } else if fee_account.amount.gt(&0) {
+      let receiver_account = match self.forfeit_receiver()? {
+        Some(_) => self.forfeit_receiver_fee_account.as_ref(),
+        None => self.fee_receiver_account.as_ref(),
+      }
+      .ok_or(FoshoErrors::AccountNotProvided)?;
+      transfer_from_vault(
+        &self.vault,
+        fee_account.to_account_info(),
+        receiver_account.to_account_info(),
+        self.fee_mint.as_ref().unwrap(),
+        fee_token_program.to_account_info(),
+        fee_account.amount,
+      )?;
+    }
+
+    self.harvest_withheld_fees()?;
+
+    let cpi_accounts = CloseAccount {
+      account: fee_account.to_account_info(),
+      destination: self.authority.to_account_info(),
+      authority: self.vault.to_account_info(),
+    };
+    close_account(CpiContext::new_with_signer(
+      fee_token_program.to_account_info(),
+      cpi_accounts,
+      &[&vault_seeds[..]],
+    ))
+  }
+
+  /// Token-2022 accounts cannot be closed while they hold withheld transfer fees,
+  /// harvesting them to the mint is permissionless.
+  fn harvest_withheld_fees(&self) -> Result<()> {
+    let fee_account = self.fee_account.as_ref().unwrap().to_account_info();
+    let fee_token_program = self.fee_token_program.as_ref().unwrap();
+    if fee_token_program.key() != spl_token_2022::ID {
+      return Ok(());
+    }
+
+    let withheld_amount = {
+      let data = fee_account.try_borrow_data()?;
+      let account = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+      account
+        .get_extension::<TransferFeeAmount>()
+        .map_or(0, |transfer_fee| u64::from(transfer_fee.withheld_amount))
+    };
+    if withheld_amount == 0 {
+      return Ok(());
+    }
+
+    let cpi_accounts = HarvestWithheldTokensToMint {
+      token_program_id: fee_token_program.to_account_info(),
+      mint: self.fee_mint.as_ref().unwrap().to_account_info(),
+    };
+    harvest_withheld_tokens_to_mint(
+      CpiContext::new(fee_token_program.to_account_info(), cpi_accounts),
+      vec![fee_account],
+    )
+  }
+
+  /// remaining accounts are passed as [account, rent receiver] pairs of the accounts of the event,
+  /// attendee records and appeals are returned to their owner, the rest to the organizer.
+  pub fn close_event_accounts(&self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    let event_accounts = remaining_accounts.chunks_exact(2);
+    require!(
+      event_accounts.remainder().is_empty(),
+      FoshoErrors::NotEnoughRemainingAccounts
+    );
+
+    for accounts in event_accounts {
+      let account_info = &accounts[0];
+      let rent_receiver = &accounts[1];
+
+      require_keys_eq!(
+        *account_info.owner,
+        crate::ID,
+        FoshoErrors::WrongAccountOwner
+      );
+      let (event, owner) = event_account_owner(&account_info.try_borrow_data()?)?;
+      assert_keys_equal(event, self.event.key())?;
+      assert_keys_equal(owner.unwrap_or(self.authority.key()), rent_receiver.key())?;
+
+      close_program_account(account_info, rent_receiver)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// event of an account closed with the event, and the owner refunded for its rent if any.
+/// invitation records are paid by their first user, who is not recorded.
+fn event_account_owner(data: &[u8]) -> Result<(Pubkey, Option<Pubkey>)> {
+  let discriminator = data
+    .get(..8)
+    .ok_or(ErrorCode::AccountDiscriminatorNotFound)?;
+  let mut data = data;
+  if discriminator == Attendee::DISCRIMINATOR {
+    let attendee_record = Attendee::try_deserialize(&mut data)?;
+    Ok((attendee_record.event, Some(attendee_record.owner)))
+  } else if discriminator == Appeal::DISCRIMINATOR {
+    let appeal = Appeal::try_deserialize(&mut data)?;
+    Ok((appeal.event, Some(appeal.owner)))
+  } else if discriminator == TicketTier::DISCRIMINATOR {
+    Ok((TicketTier::try_deserialize(&mut data)?.event, None))
+  } else if discriminator == Session::DISCRIMINATOR {
+    Ok((Session::try_deserialize(&mut data)?.event, None))
+  } else if discriminator == InvitationRecord::DISCRIMINATOR {
+    Ok((InvitationRecord::try_deserialize(&mut data)?.event, None))
+  } else {
+    Err(ErrorCode::AccountDiscriminatorMismatch.into())
+  }
+}
+
+pub fn close_event_handler<'info>(
+  ctx: Context<'_, '_, 'info, 'info, CloseEvent<'info>>,
+) -> Result<()> {
+  ctx.accounts.community.authorize(
+    &ctx.accounts.executor.key(),
+    ctx.accounts.proposal.as_deref_mut(),
+    ProposalAction::CloseEvent {
+      event: ctx.accounts.event.key(),
+    },
+  )?;
+
+  let current_time = Clock::get()?.unix_timestamp;
+
+  require!(
+    ctx.accounts.event.is_closable(current_time),
+    FoshoErrors::EventNotClosable
+  );
+  ctx.accounts.event.transition_to(EventStatus::Closed)?;
+
+  if let Some(reward_mint) = ctx.accounts.event.reward_mint {
+    if ctx.accounts.reward_mint.is_none()
+      || ctx.accounts.reward_account.is_none()
+      || ctx.accounts.receiver_account.is_none()
+    {
+      return Err(FoshoErrors::AccountNotProvided.into());
+    }
+    assert_keys_equal(
+      ctx.accounts.reward_mint.as_ref().unwrap().key(),
+      reward_mint,
+    )?;
+
+    ctx.accounts.sweep_reward_tokens()?;
+  }
+
+  if let Some(fee_mint) = ctx.accounts.event.fee_mint {
+    if ctx.accounts.fee_mint.is_none()
+      || ctx.accounts.fee_account.is_none()
+      || ctx.accounts.fee_token_program.is_none()
+    {
+      return Err(FoshoErrors::AccountNotProvided.into());
+    }
+    assert_keys_equal(ctx.accounts.fee_mint.as_ref().unwrap().key(), fee_mint)?;
+
+    ctx.accounts.sweep_fee_tokens()?;
+  } else {
+    ctx.accounts.sweep_unclaimed_lamports()?;
+  }
+
+  ctx.accounts.close_event_accounts(ctx.remaining_accounts)?;
+
+  // leftover lamports of the event account are sent to the authority on close
+  Ok(())
+}
@@ -0,0 +1,59 @@
+use crate::{constant::*, error::FoshoErrors, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ProposeCommunityAuthority<'info> {
+  #[account(
+    mut,
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Account<'info, Community>,
+  /// required once the governance of the community is enabled
+  #[account(mut, has_one = community)]
+  pub proposal: Option<Box<Account<'info, Proposal>>>,
+  /// community authority, or anyone executing an approved proposal
+  pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptCommunityAuthority<'info> {
+  #[account(
+    mut,
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+    constraint = community.pending_authority == Some(new_authority.key()) @ FoshoErrors::InvalidPendingAuthority,
+  )]
+  pub community: Account<'info, Community>,
+  pub new_authority: Signer<'info>,
+}
+
+// the transfer only happens once the new authority signs,
+// proposing None cancels a pending transfer.
+pub fn propose_community_authority_handler(
+  ctx: Context<ProposeCommunityAuthority>,
+  new_authority: Option<Pubkey>,
+) -> Result<()> {
+  let community = &mut ctx.accounts.community;
+  community.authorize(
+    &ctx.accounts.authority.key(),
+    ctx.accounts.proposal.as_deref_mut(),
+    ProposalAction::ChangeAuthority { new_authority },
+  )?;
+
+  community.pending_authority = new_authority;
+  Ok(())
+}
+
+pub fn accept_community_authority_handler(ctx: Context<AcceptCommunityAuthority>) -> Result<()> {
+  let community = &mut ctx.accounts.community;
+  community.authority = ctx.accounts.new_authority.key();
+  community.pending_authority = None;
+  Ok(())
+}
@@ -34,5 +34,15 @@ pub fn create_community_handler(
   community.bump = ctx.bumps.community;
   community.seed = seed;
   community.name = name;
-  Ok(())
+  community.pending_authority = None;
+  community.uri = String::new();
+  community.logo = String::new();
+  community.social_links = vec![];
+  community.approvers = vec![];
+  community.threshold = 0;
+  community.proposals_count = 0;
+  community.membership_fee = 0;
+  community.membership_requires_approval = false;
+  community.members_count = 0;
+  community.validate()
 }
@@ -1,4 +1,9 @@
-use crate::{constant::*, error::FoshoErrors, state::*, utils::create_attribute};
+use crate::{
+  constant::*,
+  error::FoshoErrors,
+  state::*,
+  utils::{create_attribute, validate_event_start_time},
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
   associated_token::AssociatedToken,
@@ -64,6 +69,33 @@ pub struct CreateEvent<'info> {
     associated_token::token_program = token_program
   )]
   pub sender_account: Option<InterfaceAccount<'info, TokenAccount>>,
+  /// escrow of the commitment fees
+  #[account(
+    init,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump,
+    payer = authority,
+    space = 8 + EventVault::INIT_SPACE
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
+  /// commitment fees are paid in this token if provided, in SOL otherwise
+  #[account(
+    mint::token_program = fee_token_program,
+  )]
+  pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+  #[account(
+    init,
+    payer = authority,
+    associated_token::mint = fee_mint,
+    associated_token::authority = vault,
+    associated_token::token_program = fee_token_program
+  )]
+  pub fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  pub fee_token_program: Option<Interface<'info, TokenInterface>>,
   #[account(mut)]
   pub authority: Signer<'info>,
   pub token_program: Interface<'info, TokenInterface>,
@@ -95,21 +127,20 @@ impl<'info> CreateEvent<'info> {
     event_type: EventType,
     organizer: String,
     commitment_fee: u64,
-    event_starts_at: Option<i64>,
-    event_ends_at: Option<i64>,
-    registration_starts_at: Option<i64>,
-    registration_ends_at: Option<i64>,
-    capacity: Option<u64>,
     location: Option<String>,
     virtual_link: Option<String>,
     description: Option<String>,
     event_collection_bump: u8,
   ) -> Result<()> {
+    let schedule = &self.event.schedule;
     let mut attribute_list = vec![
       create_attribute("Event Type", format!("{:?}", event_type)),
       create_attribute("Organizer", organizer.to_string()),
       create_attribute("Fee", commitment_fee.to_string()),
     ];
+    if let Some(fee_mint) = &self.fee_mint {
+      attribute_list.push(create_attribute("Fee Mint", fee_mint.key().to_string()));
+    }
 
     macro_rules! add_optional_attribute {
       ($key:expr, $value:expr) => {
@@ -119,32 +150,12 @@ impl<'info> CreateEvent<'info> {
       };
     }
 
-    let event_start_time = if event_starts_at.is_some() {
-      let event_start_time_unwraped = event_starts_at.unwrap();
-      let clock = Clock::get().unwrap();
-      let current_time = clock.unix_timestamp;
-
-      require_gt!(
-        event_start_time_unwraped,
-        current_time,
-        FoshoErrors::InvalidEventStartTime
-      );
-      event_start_time_unwraped
-    } else {
-      0
-    };
-
-    if let Some(reg_end_time) = registration_ends_at {
-      if event_start_time.gt(&reg_end_time) {
-        return Err(FoshoErrors::InvalidRegistrationEndTime.into());
-      }
-    };
-
-    add_optional_attribute!("Event Starts At", Some(event_start_time as u64));
-    add_optional_attribute!("Event Ends At", event_ends_at);
-    add_optional_attribute!("Registration Starts At", registration_starts_at);
-    add_optional_attribute!("Registration Ends At", registration_ends_at);
-    add_optional_attribute!("Capacity", capacity);
+    // display copy of the event schedule
+    add_optional_attribute!("Event Starts At", Some(schedule.starts_at.unwrap_or(0)));
+    add_optional_attribute!("Event Ends At", schedule.ends_at);
+    add_optional_attribute!("Registration Starts At", schedule.registration_starts_at);
+    add_optional_attribute!("Registration Ends At", schedule.registration_ends_at);
+    add_optional_attribute!("Capacity", schedule.capacity);
     add_optional_attribute!("Location", location);
     add_optional_attribute!("Virtual Link", virtual_link);
     add_optional_attribute!("Description", description);
@@ -194,7 +205,7 @@ pub fn create_event_handler(
   reward_per_user: u64,
   // event_authorities can sign join_event ixn
   // and the verify_attendance ixn
-  event_authorities: Vec<Pubkey>,
+  event_authorities: Vec<EventAuthority>,
   // authorities must sign join_event ixn
   authority_must_sign: bool,
 ) -> Result<()> {
@@ -208,40 +219,91 @@ pub fn create_event_handler(
   } else {
     None
   };
-  let max_attendees = capacity.unwrap_or(1);
   event.commitment_fee = commitment_fee;
+  event.fee_mint = ctx
+    .accounts
+    .fee_mint
+    .as_ref()
+    .map(|fee_mint| fee_mint.key());
+  if event.fee_mint.is_some() && ctx.accounts.fee_account.is_none() {
+    return Err(FoshoErrors::AccountNotProvided.into());
+  }
 
   // handled by the event collection
-  // event.max_attendees = max_attendees as u32;
   // event.event_start_time = event_start_time;
 
+  let vault = &mut ctx.accounts.vault;
+  vault.event = event.key();
+  vault.bump = ctx.bumps.vault;
+  vault.total_deposited = 0;
+  vault.total_refunded = 0;
+  vault.total_forfeited = 0;
+  vault.verified_fees = 0;
+  vault.forfeit_pool = 0;
+  vault.partial_refunds = 0;
+
   event.community = community.key();
   event.nonce = ctx.accounts.community.events_count;
   event.bump = ctx.bumps.event;
-  event.is_cancelled = false;
+  event.status = EventStatus::Draft;
+  event.attendees_count = 0;
+  event.claimed_count = 0;
+  event.settled_at = None;
+  event.refund_policy = RefundPolicy::default();
+  event.forfeit_policy = ForfeitPolicy::default();
+  event.check_in_mode = CheckInMode::default();
+  event.sessions_count = 0;
+  event.settlement_rule = SettlementRule::default();
+  event.check_out_policy = CheckOutPolicy::default();
+  event.amendment_window = 0;
+  event.tiers_count = 0;
   event.reward_per_user = reward_per_user;
+  event.reward_liability = 0;
   event.authority_must_sign = authority_must_sign;
+  Event::validate_event_authorities(&event_authorities)?;
   event.event_authorities = event_authorities;
+  event_version.validate()?;
   event.event_version = event_version;
 
+  if let Some(event_start_time) = event_starts_at {
+    validate_event_start_time(event_start_time)?;
+  }
+  // 0 stands for an unlimited capacity, same as update_event
+  let capacity = capacity.filter(|capacity| capacity.gt(&0));
+  event.schedule = EventSchedule {
+    starts_at: event_starts_at,
+    ends_at: event_ends_at,
+    registration_starts_at,
+    registration_ends_at,
+    capacity,
+  };
+  event.schedule.validate()?;
+
   let community_mut = &mut ctx.accounts.community;
   community_mut.events_count += 1;
 
   if reward_per_user.gt(&0) {
-    if ctx.accounts.reward_mint.is_none()
-      || ctx.accounts.reward_account.is_none()
-      || ctx.accounts.sender_account.is_none()
-    {
+    if ctx.accounts.reward_mint.is_none() || ctx.accounts.reward_account.is_none() {
       return Err(FoshoErrors::AccountNotProvided.into());
     }
 
-    let total_reward = reward_per_user.checked_mul(max_attendees as u64).unwrap();
+    // the capacity of the event is prefunded, ticket tiers share this deposit.
+    // without a capacity the rewards are funded with fund_rewards,
+    // join_event fails as long as the next reward is not funded.
+    if let Some(capacity) = capacity {
+      if ctx.accounts.sender_account.is_none() {
+        return Err(FoshoErrors::AccountNotProvided.into());
+      }
+      let total_reward = reward_per_user
+        .checked_mul(capacity)
+        .ok_or(FoshoErrors::NumericalOverflow)?;
 
-    transfer_checked(
-      ctx.accounts.deposit_reward_tokens(),
-      total_reward,
-      reward_mint.unwrap().decimals,
-    )?;
+      transfer_checked(
+        ctx.accounts.deposit_reward_tokens(),
+        total_reward,
+        reward_mint.unwrap().decimals,
+      )?;
+    }
   }
   // creates the event collection
   ctx.accounts.create_event_collection(
@@ -250,11 +312,6 @@ pub fn create_event_handler(
     event_type,
     organizer,
     commitment_fee,
-    event_starts_at,
-    event_ends_at,
-    registration_starts_at,
-    registration_ends_at,
-    capacity,
     location,
     virtual_link,
     description,
@@ -0,0 +1,79 @@
+use crate::{constant::*, error::FoshoErrors, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CreateSession<'info> {
+  #[account(
+    init,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      SESSION_SUFFIX_SEED,
+      &[event.sessions_count],
+    ],
+    bump,
+    payer = authority,
+    space = 8 + Session::INIT_SPACE
+  )]
+  pub session: Box<Account<'info, Session>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+    has_one = authority
+  )]
+  pub community: Box<Account<'info, Community>>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+// Option::is_none_or is newer than the rustc of the SBF toolchain
+#[allow(clippy::unnecessary_map_or)]
+pub fn create_session_handler(
+  ctx: Context<CreateSession>,
+  starts_at: i64,
+  ends_at: i64,
+  location: Option<String>,
+) -> Result<()> {
+  let event = &mut ctx.accounts.event;
+
+  event.assert_allows(EventAction::Update)?;
+  // attendees joined for the sessions announced at the time
+  require_eq!(event.attendees_count, 0, FoshoErrors::SessionsLocked);
+  require!(
+    event.sessions_count < MAX_SESSIONS
+      && starts_at < ends_at
+      && location
+        .as_ref()
+        .map_or(true, |location| location.len() <= MAX_SESSION_LOCATION_LEN),
+    FoshoErrors::InvalidSession
+  );
+
+  let session = &mut ctx.accounts.session;
+  session.event = event.key();
+  session.index = event.sessions_count;
+  session.bump = ctx.bumps.session;
+  session.starts_at = starts_at;
+  session.ends_at = ends_at;
+  session.location = location;
+  session.attendees_count = 0;
+
+  event.sessions_count = event.sessions_count.checked_add(1).unwrap();
+  event.settlement_rule.validate(event.sessions_count)?;
+
+  Ok(())
+}
@@ -0,0 +1,103 @@
+use crate::{constant::*, error::FoshoErrors, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenInterface};
+
+#[derive(Accounts)]
+pub struct CreateTicketTier<'info> {
+  #[account(
+    init,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      TICKET_TIER_SUFFIX_SEED,
+      &[event.tiers_count],
+    ],
+    bump,
+    payer = authority,
+    space = 8 + TicketTier::INIT_SPACE
+  )]
+  pub ticket_tier: Box<Account<'info, TicketTier>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+    has_one = authority
+  )]
+  pub community: Box<Account<'info, Community>>,
+  /// required to check the reward token of rewarded tiers
+  #[account(
+    mint::token_program = token_program,
+  )]
+  pub reward_mint: Option<InterfaceAccount<'info, Mint>>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub token_program: Interface<'info, TokenInterface>,
+  pub system_program: Program<'info, System>,
+}
+
+pub fn create_ticket_tier_handler(
+  ctx: Context<CreateTicketTier>,
+  name: String,
+  uri: String,
+  commitment_fee: u64,
+  reward_per_user: u64,
+  capacity: Option<u64>,
+  event_version: EventVersion,
+) -> Result<()> {
+  let event = &mut ctx.accounts.event;
+
+  event.assert_allows(EventAction::Update)?;
+  require!(
+    name.len() <= MAX_TICKET_TIER_NAME_LEN && uri.len() <= MAX_TICKET_TIER_URI_LEN,
+    FoshoErrors::InvalidTicketTierMetadata
+  );
+
+  let ticket_tier = &mut ctx.accounts.ticket_tier;
+  ticket_tier.event = event.key();
+  ticket_tier.index = event.tiers_count;
+  ticket_tier.bump = ctx.bumps.ticket_tier;
+  ticket_tier.name = name;
+  ticket_tier.uri = uri;
+  ticket_tier.commitment_fee = commitment_fee;
+  ticket_tier.reward_per_user = reward_per_user;
+  // 0 stands for an unlimited capacity
+  let capacity = capacity.filter(|capacity| capacity.gt(&0));
+  ticket_tier.capacity = capacity;
+  ticket_tier.attendees_count = 0;
+  event_version.validate()?;
+  ticket_tier.event_version = event_version;
+
+  event.tiers_count = event
+    .tiers_count
+    .checked_add(1)
+    .ok_or(FoshoErrors::NumericalOverflow)?;
+
+  // tier rewards are paid out of the reward account of the event. Only the capacity
+  // of the event is prefunded on creation, fund_rewards covers the rest.
+  if reward_per_user.gt(&0) {
+    let reward_mint = ctx
+      .accounts
+      .reward_mint
+      .as_ref()
+      .ok_or(FoshoErrors::AccountNotProvided)?;
+    require!(
+      ctx.accounts.event.reward_mint == Some(reward_mint.key()),
+      FoshoErrors::PublicKeyMismatch
+    );
+  }
+
+  Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::{constant::*, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ManageEventAuthority<'info> {
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Account<'info, Event>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+    has_one = authority
+  )]
+  pub community: Account<'info, Community>,
+  pub authority: Signer<'info>,
+}
+
+pub fn add_event_authority_handler(
+  ctx: Context<ManageEventAuthority>,
+  event_authority: Pubkey,
+  roles: u8,
+) -> Result<()> {
+  let event = &mut ctx.accounts.event;
+  event.event_authorities.push(EventAuthority {
+    key: event_authority,
+    roles,
+  });
+  Event::validate_event_authorities(&event.event_authorities)
+}
+
+// revokes the key right away, e.g. when a device of the door staff is lost
+pub fn remove_event_authority_handler(
+  ctx: Context<ManageEventAuthority>,
+  event_authority: Pubkey,
+) -> Result<()> {
+  let event = &mut ctx.accounts.event;
+  let index = event.position_of_event_authority(&event_authority)?;
+  event.event_authorities.remove(index);
+  Ok(())
+}
+
+// the new key keeps the roles of the replaced one
+pub fn replace_event_authority_handler(
+  ctx: Context<ManageEventAuthority>,
+  old_event_authority: Pubkey,
+  new_event_authority: Pubkey,
+) -> Result<()> {
+  let event = &mut ctx.accounts.event;
+  let index = event.position_of_event_authority(&old_event_authority)?;
+  event.event_authorities[index].key = new_event_authority;
+  Event::validate_event_authorities(&event.event_authorities)
+}
+
+pub fn set_event_authority_roles_handler(
+  ctx: Context<ManageEventAuthority>,
+  event_authority: Pubkey,
+  roles: u8,
+) -> Result<()> {
+  let event = &mut ctx.accounts.event;
+  let index = event.position_of_event_authority(&event_authority)?;
+  event.event_authorities[index].roles = roles;
+  Event::validate_event_authorities(&event.event_authorities)
+}
@@ -0,0 +1,105 @@
+use crate::{constant::*, error::FoshoErrors, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateEventStatus<'info> {
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Account<'info, Event>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+    has_one = authority
+  )]
+  pub community: Account<'info, Community>,
+  pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleEvent<'info> {
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Account<'info, Event>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+  )]
+  pub vault: Account<'info, EventVault>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+    has_one = authority
+  )]
+  pub community: Account<'info, Community>,
+  pub authority: Signer<'info>,
+}
+
+// opens the registration
+pub fn publish_event_handler(ctx: Context<UpdateEventStatus>) -> Result<()> {
+  ctx.accounts.event.transition_to(EventStatus::Published)
+}
+
+// opens the check-in once the event starts, registration stays open until the registration window closes
+pub fn start_check_in_handler(ctx: Context<UpdateEventStatus>) -> Result<()> {
+  let event = &mut ctx.accounts.event;
+  event
+    .schedule
+    .assert_started(Clock::get()?.unix_timestamp)?;
+  event.transition_to(EventStatus::Live)
+}
+
+// closes the check-in once the event is over, attendees that did not show up can still be rejected
+pub fn end_event_handler(ctx: Context<UpdateEventStatus>) -> Result<()> {
+  let event = &mut ctx.accounts.event;
+  event.schedule.assert_over(Clock::get()?.unix_timestamp)?;
+  event.transition_to(EventStatus::Ended)
+}
+
+// attendance is final from here on, claims are opened
+pub fn settle_event_handler(ctx: Context<SettleEvent>) -> Result<()> {
+  let event = &mut ctx.accounts.event;
+  let current_time = Clock::get()?.unix_timestamp;
+  event.assert_settleable(current_time)?;
+  event.transition_to(EventStatus::Settled)?;
+  event.settled_at = Some(current_time);
+
+  // every fee that is not owed to a verified attendee is forfeited,
+  // without verified attendees the organizer claims them instead.
+  let vault = &mut ctx.accounts.vault;
+  if event.forfeit_policy == ForfeitPolicy::ProRata && vault.verified_fees.gt(&0) {
+    vault.forfeit_pool = vault
+      .outstanding()?
+      .checked_sub(vault.verified_fees)
+      .and_then(|pool| pool.checked_sub(vault.partial_refunds))
+      .ok_or(FoshoErrors::NumericalOverflow)?;
+  }
+  Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::{constant::*, error::FoshoErrors, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+  transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+  #[account(
+    seeds = [
+      EVENT_PRE_SEED,
+      event.community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    constraint = event.reward_mint == Some(reward_mint.key()) @ FoshoErrors::PublicKeyMismatch,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    mint::token_program = token_program,
+  )]
+  pub reward_mint: InterfaceAccount<'info, Mint>,
+  #[account(
+    mut,
+    associated_token::mint = reward_mint,
+    associated_token::authority = event,
+    associated_token::token_program = token_program
+  )]
+  pub reward_account: InterfaceAccount<'info, TokenAccount>,
+  #[account(
+    mut,
+    associated_token::mint = reward_mint,
+    associated_token::authority = funder,
+    associated_token::token_program = token_program
+  )]
+  pub funder_account: InterfaceAccount<'info, TokenAccount>,
+  /// organizer or sponsor of the event
+  pub funder: Signer<'info>,
+  pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FundRewards<'info> {
+  pub fn deposit_reward_tokens(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+    let cpi_accounts = TransferChecked {
+      from: self.funder_account.to_account_info(),
+      to: self.reward_account.to_account_info(),
+      mint: self.reward_mint.to_account_info(),
+      authority: self.funder.to_account_info(),
+    };
+
+    let cpi_program = self.token_program.to_account_info();
+
+    CpiContext::new(cpi_program, cpi_accounts)
+  }
+}
+
+// tops up the reward account of the event, anyone can fund the rewards
+pub fn fund_rewards_handler(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+  transfer_checked(
+    ctx.accounts.deposit_reward_tokens(),
+    amount,
+    ctx.accounts.reward_mint.decimals,
+  )
+}
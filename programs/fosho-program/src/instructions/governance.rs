@@ -0,0 +1,184 @@
+use crate::{constant::*, error::FoshoErrors, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+  #[account(
+    init,
+    seeds = [
+      PROPOSAL_PRE_SEED,
+      community.key().as_ref(),
+      &community.proposals_count.to_le_bytes()
+    ],
+    bump,
+    payer = proposer,
+    space = 8 + Proposal::INIT_SPACE
+  )]
+  pub proposal: Box<Account<'info, Proposal>>,
+  #[account(
+    mut,
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  #[account(mut)]
+  pub proposer: Signer<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+  #[account(
+    mut,
+    seeds = [
+      PROPOSAL_PRE_SEED,
+      community.key().as_ref(),
+      &proposal.index.to_le_bytes()
+    ],
+    bump = proposal.bump,
+    has_one = community,
+  )]
+  pub proposal: Box<Account<'info, Proposal>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProposal<'info> {
+  #[account(
+    mut,
+    seeds = [
+      PROPOSAL_PRE_SEED,
+      community.key().as_ref(),
+      &proposal.index.to_le_bytes()
+    ],
+    bump = proposal.bump,
+    has_one = community,
+    has_one = proposer,
+    close = proposer,
+  )]
+  pub proposal: Box<Account<'info, Proposal>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  /// CHECK: checked by has_one on the proposal, receives the rent
+  #[account(mut)]
+  pub proposer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetApprovers<'info> {
+  #[account(
+    mut,
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  /// required once the governance is enabled
+  #[account(mut, has_one = community)]
+  pub proposal: Option<Box<Account<'info, Proposal>>>,
+  pub authority: Signer<'info>,
+}
+
+// any approver, or the community authority, can open a proposal
+pub fn create_proposal_handler(ctx: Context<CreateProposal>, action: ProposalAction) -> Result<()> {
+  let community = &mut ctx.accounts.community;
+  let proposer = ctx.accounts.proposer.key();
+  let is_approver = community.approvers.contains(&proposer);
+  require!(
+    is_approver || proposer == community.authority,
+    FoshoErrors::NotAnApprover
+  );
+
+  let proposal = &mut ctx.accounts.proposal;
+  proposal.community = community.key();
+  proposal.index = community.proposals_count;
+  proposal.bump = ctx.bumps.proposal;
+  proposal.proposer = proposer;
+  proposal.action = action;
+  // the proposer approves their own proposal
+  proposal.approvals = if is_approver { vec![proposer] } else { vec![] };
+  proposal.executed = false;
+  proposal.created_at = Clock::get()?.unix_timestamp;
+
+  community.proposals_count = community.proposals_count.checked_add(1).unwrap();
+  Ok(())
+}
+
+pub fn approve_proposal_handler(ctx: Context<ApproveProposal>) -> Result<()> {
+  let approver = ctx.accounts.approver.key();
+  let proposal = &mut ctx.accounts.proposal;
+
+  require!(
+    ctx.accounts.community.approvers.contains(&approver),
+    FoshoErrors::NotAnApprover
+  );
+  require!(!proposal.executed, FoshoErrors::ProposalAlreadyExecuted);
+  require!(
+    !proposal.is_expired(Clock::get()?.unix_timestamp),
+    FoshoErrors::ProposalExpired
+  );
+  require!(
+    !proposal.approvals.contains(&approver),
+    FoshoErrors::AlreadyApproved
+  );
+
+  // approvals of removed approvers would take the room of the current ones
+  let approvers = &ctx.accounts.community.approvers;
+  proposal
+    .approvals
+    .retain(|approval| approvers.contains(approval));
+  proposal.approvals.push(approver);
+  Ok(())
+}
+
+// the rent goes back to the proposer once the proposal cannot be executed anymore
+pub fn close_proposal_handler(ctx: Context<CloseProposal>) -> Result<()> {
+  let proposal = &ctx.accounts.proposal;
+  require!(
+    proposal.executed || proposal.is_expired(Clock::get()?.unix_timestamp),
+    FoshoErrors::ProposalStillOpen
+  );
+  Ok(())
+}
+
+// a threshold of 0 disables the governance
+pub fn set_approvers_handler(
+  ctx: Context<SetApprovers>,
+  approvers: Vec<Pubkey>,
+  threshold: u8,
+) -> Result<()> {
+  Community::validate_approvers(&approvers, threshold)?;
+
+  let community = &mut ctx.accounts.community;
+  community.authorize(
+    &ctx.accounts.authority.key(),
+    ctx.accounts.proposal.as_deref_mut(),
+    ProposalAction::SetApprovers {
+      approvers: approvers.clone(),
+      threshold,
+    },
+  )?;
+
+  community.approvers = approvers;
+  community.threshold = threshold;
+  Ok(())
+}
@@ -3,26 +3,49 @@ use crate::{
   error::FoshoErrors,
   state::*,
   utils::{
-    assert_is_ata, create_attribute, create_ticket_plugins, get_capacity_from_attributes,
-    get_reg_ends_at_from_attributes, get_reg_starts_at_from_attributes, get_spl_token_amount,
-    validate_nft_collection, validate_verified_nft_creator,
+    assert_is_ata, assert_keys_equal, assert_owned_by, create_attribute, create_ticket_plugins,
+    get_spl_token_amount, load_core_asset, validate_core_asset_attribute,
+    validate_core_asset_collection, validate_core_asset_creator, validate_nft_collection,
+    validate_verified_nft_creator, verify_ed25519_signature,
   },
 };
 use anchor_lang::{
   prelude::*,
+  solana_program::sysvar::instructions as sysvar_instructions,
   system_program::{transfer, Transfer},
 };
-
-use anchor_spl::token_interface::TokenInterface;
-use mpl_core::{
-  accounts::BaseCollectionV1,
-  fetch_plugin,
-  instructions::CreateV2CpiBuilder,
-  types::{Attributes, PluginType},
-  ID as MPL_CORE_ID,
+use fosho_allowlist::{verify_proof, AllowlistEntry};
+use std::slice::Iter;
+
+use anchor_spl::{
+  metadata::{MetadataAccount, ID as METADATA_PROGRAM_ID},
+  token::ID as TOKEN_PROGRAM_ID,
+  token_2022::ID as TOKEN_2022_PROGRAM_ID,
+  token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
+use mpl_core::{accounts::BaseCollectionV1, instructions::CreateV2CpiBuilder, ID as MPL_CORE_ID};
+
+/// Entry of the attendee on the allowlist and its merkle proof
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AllowlistProof {
+  pub tier: Option<u8>,
+  pub discount_bps: u16,
+  pub proof: Vec<[u8; 32]>,
+}
+
+impl AllowlistProof {
+  pub fn verify(&self, wallet: &Pubkey, merkle_root: &[u8; 32]) -> bool {
+    let entry = AllowlistEntry {
+      wallet: *wallet,
+      tier: self.tier,
+      discount_bps: self.discount_bps,
+    };
+    self.discount_bps <= BPS_DENOMINATOR && verify_proof(merkle_root, entry.leaf(), &self.proof)
+  }
+}
 
 #[derive(Accounts)]
+#[instruction(allowlist_proof: Option<AllowlistProof>, invitation: Option<Invitation>)]
 pub struct JoinEvent<'info> {
   #[account(
     init,
@@ -46,6 +69,17 @@ pub struct JoinEvent<'info> {
     bump = event.bump,
   )]
   pub event: Box<Account<'info, Event>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
   #[account(
     seeds = [
       COMMUNITY_PRE_SEED.as_ref(),
@@ -65,6 +99,32 @@ pub struct JoinEvent<'info> {
       constraint = event_collection.update_authority == community.key(),
   )]
   pub event_collection: Box<Account<'info, BaseCollectionV1>>,
+  /// required if the event has ticket tiers
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      TICKET_TIER_SUFFIX_SEED,
+      &[ticket_tier.index],
+    ],
+    bump = ticket_tier.bump,
+    has_one = event @ FoshoErrors::InvalidTicketTier,
+  )]
+  pub ticket_tier: Option<Box<Account<'info, TicketTier>>>,
+  /// created on the first join in the community, read by attendance gates
+  #[account(
+    init_if_needed,
+    payer = attendee,
+    space = 8 + AttendanceHistory::INIT_SPACE,
+    seeds = [
+      ATTENDANCE_PRE_SEED,
+      event.community.as_ref(),
+      attendee.key().as_ref()
+    ],
+    bump,
+  )]
+  pub attendance_history: Box<Account<'info, AttendanceHistory>>,
   /// CHECK: checked against the event authority in the create_event instruction
   /// if it exists they would have to sign this transaction
   pub event_authority: AccountInfo<'info>,
@@ -85,13 +145,141 @@ pub struct JoinEvent<'info> {
   /// CHECK: This is checked by the address constraint
   pub mpl_core_program: UncheckedAccount<'info>,
   pub token_program: Interface<'info, TokenInterface>,
+  /// required if the attendee is rewarded, to check that the reward is funded
+  #[account(
+    associated_token::mint = event.reward_mint.unwrap_or_default(),
+    associated_token::authority = event,
+    associated_token::token_program = token_program
+  )]
+  pub reward_account: Option<InterfaceAccount<'info, TokenAccount>>,
+  /// the fee accounts are required if the commitment fee is paid in tokens
+  #[account(
+    mint::token_program = fee_token_program,
+  )]
+  pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = vault,
+    associated_token::token_program = fee_token_program
+  )]
+  pub fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = attendee,
+    associated_token::token_program = fee_token_program
+  )]
+  pub attendee_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  pub fee_token_program: Option<Interface<'info, TokenInterface>>,
+  /// required to join with an invitation instead of the event authority signature
+  /// CHECK: checked by the address constraint
+  #[account(address = sysvar_instructions::ID)]
+  pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+  #[account(
+    init_if_needed,
+    payer = attendee,
+    space = 8 + InvitationRecord::INIT_SPACE,
+    seeds = [
+      INVITATION_PRE_SEED,
+      event.key().as_ref(),
+      event_authority.key().as_ref(),
+      &invitation.as_ref().map_or(0, |invitation| invitation.nonce).to_le_bytes()
+    ],
+    bump,
+  )]
+  pub invitation_record: Option<Box<Account<'info, InvitationRecord>>>,
 }
 
 impl<'info> JoinEvent<'info> {
+  pub fn commitment_fee(&self) -> u64 {
+    match &self.ticket_tier {
+      Some(ticket_tier) => ticket_tier.commitment_fee,
+      None => self.event.commitment_fee,
+    }
+  }
+
+  /// the invitation replaces the signature of the event authority,
+  /// the Ed25519 instruction verifying it has to precede this instruction.
+  pub fn use_invitation(&mut self, invitation: &Invitation, invitation_bump: u8) -> Result<()> {
+    require_keys_eq!(
+      invitation.event,
+      self.event.key(),
+      FoshoErrors::InvalidInvitation
+    );
+    if let Some(invitee) = invitation.invitee {
+      require_keys_eq!(invitee, self.attendee.key(), FoshoErrors::InvalidInvitation);
+    }
+    if let Some(tier) = invitation.tier {
+      let ticket_tier = self
+        .ticket_tier
+        .as_ref()
+        .ok_or(FoshoErrors::TicketTierRequired)?;
+      require_eq!(ticket_tier.index, tier, FoshoErrors::InvalidTicketTier);
+    }
+    require_gte!(
+      invitation.expires_at,
+      Clock::get()?.unix_timestamp,
+      FoshoErrors::InvitationExpired
+    );
+
+    let instructions_sysvar = self
+      .instructions_sysvar
+      .as_ref()
+      .ok_or(FoshoErrors::AccountNotProvided)?;
+    verify_ed25519_signature(
+      instructions_sysvar,
+      &self.event_authority.key(),
+      &invitation.message()?,
+    )?;
+
+    // the record stops replays beyond the max uses of the invitation
+    let invitation_record = self
+      .invitation_record
+      .as_mut()
+      .ok_or(FoshoErrors::AccountNotProvided)?;
+    if invitation_record.uses == 0 {
+      invitation_record.event = self.event.key();
+      invitation_record.authority = self.event_authority.key();
+      invitation_record.nonce = invitation.nonce;
+      invitation_record.bump = invitation_bump;
+    }
+    require_gt!(
+      invitation.max_uses,
+      invitation_record.uses,
+      FoshoErrors::InvitationUsedUp
+    );
+    invitation_record.uses += 1;
+    Ok(())
+  }
+
+  /// the proof if it matches an allowlist of the event or of the ticket tier
+  pub fn verified_allowlist_proof<'p>(
+    &self,
+    allowlist_proof: Option<&'p AllowlistProof>,
+  ) -> Option<&'p AllowlistProof> {
+    let allowlist_proof = allowlist_proof?;
+    let mut roots = self.event.event_version.allowlist_roots();
+    if let Some(ticket_tier) = &self.ticket_tier {
+      roots.extend(ticket_tier.event_version.allowlist_roots());
+    }
+    roots
+      .iter()
+      .any(|root| allowlist_proof.verify(&self.attendee.key(), root))
+      .then_some(allowlist_proof)
+  }
+
+  pub fn reward_amount(&self) -> u64 {
+    match &self.ticket_tier {
+      Some(ticket_tier) => ticket_tier.reward_per_user,
+      None => self.event.reward_per_user,
+    }
+  }
+
   pub fn transfer_commitment_fee(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
     let cpi_accounts = Transfer {
       from: self.attendee.to_account_info(),
-      to: self.event.to_account_info(),
+      to: self.vault.to_account_info(),
     };
 
     let cpi_program = self.system_program.to_account_info();
@@ -99,48 +287,82 @@ impl<'info> JoinEvent<'info> {
     CpiContext::new(cpi_program, cpi_accounts)
   }
 
-  pub fn create_event_ticket(&self, ticket_bump: u8) -> Result<()> {
-    // Check that the maximum number of tickets has not been reached yet
-    let (_, collection_attribute_list, _) = fetch_plugin::<BaseCollectionV1, Attributes>(
-      &self.event_collection.to_account_info(),
-      PluginType::Attributes,
+  /// returns the amount received by the vault,
+  /// it is lower than the commitment fee for mints with a transfer fee.
+  pub fn deposit_commitment_fee_tokens(&mut self, commitment_fee: u64) -> Result<u64> {
+    if self.fee_mint.is_none()
+      || self.fee_account.is_none()
+      || self.attendee_fee_account.is_none()
+      || self.fee_token_program.is_none()
+    {
+      return Err(FoshoErrors::AccountNotProvided.into());
+    }
+    let fee_mint = self.fee_mint.as_ref().unwrap();
+    require!(
+      self.event.fee_mint == Some(fee_mint.key()),
+      FoshoErrors::PublicKeyMismatch
+    );
+
+    let balance_before = self.fee_account.as_ref().unwrap().amount;
+
+    let cpi_accounts = TransferChecked {
+      from: self
+        .attendee_fee_account
+        .as_ref()
+        .unwrap()
+        .to_account_info(),
+      to: self.fee_account.as_ref().unwrap().to_account_info(),
+      mint: fee_mint.to_account_info(),
+      authority: self.attendee.to_account_info(),
+    };
+    let cpi_program = self.fee_token_program.as_ref().unwrap().to_account_info();
+    transfer_checked(
+      CpiContext::new(cpi_program, cpi_accounts),
+      commitment_fee,
+      fee_mint.decimals,
     )?;
 
-    let capacity = get_capacity_from_attributes(&collection_attribute_list.attribute_list)?;
-    let reg_starts_at =
-      get_reg_starts_at_from_attributes(&collection_attribute_list.attribute_list)?;
-    let reg_ends_at = get_reg_ends_at_from_attributes(&collection_attribute_list.attribute_list)?;
+    let fee_account = self.fee_account.as_mut().unwrap();
+    fee_account.reload()?;
+    fee_account
+      .amount
+      .checked_sub(balance_before)
+      .ok_or(FoshoErrors::NumericalOverflow.into())
+  }
 
-    if capacity.ne(&0) {
+  pub fn create_event_ticket(&self, ticket_bump: u8) -> Result<()> {
+    // Check that the maximum number of tickets has not been reached yet,
+    // tickets burned by unjoin_event free their seat.
+    let schedule = &self.event.schedule;
+    if let Some(capacity) = schedule.capacity {
       require!(
-        self.event_collection.num_minted < capacity,
+        u64::from(self.event.attendees_count) < capacity,
         FoshoErrors::MaximumTicketsReached
       );
     }
 
-    let current_unix_ts = Clock::get()?.unix_timestamp as u64;
-    if reg_starts_at.ne(&0) {
-      require!(
-        current_unix_ts >= reg_starts_at,
-        FoshoErrors::RegistrationNotStarted
-      );
+    if let Some(ticket_tier) = &self.ticket_tier {
+      if let Some(tier_capacity) = ticket_tier.capacity {
+        require!(
+          u64::from(ticket_tier.attendees_count) < tier_capacity,
+          FoshoErrors::MaximumTicketsReached
+        );
+      }
     }
 
-    if reg_ends_at.ne(&0) {
-      require!(
-        current_unix_ts <= reg_ends_at,
-        FoshoErrors::RegistrationEnded
-      );
-    }
+    schedule.assert_registration_open(Clock::get()?.unix_timestamp)?;
 
     // Create ticket attributes
-    let attribute_list = vec![
+    let mut attribute_list = vec![
       create_attribute(
         "Ticket Number",
         (self.event_collection.num_minted + 1).to_string(),
       ),
-      create_attribute("Fee", self.event.commitment_fee.to_string()),
+      create_attribute("Fee", self.commitment_fee().to_string()),
     ];
+    if let Some(ticket_tier) = &self.ticket_tier {
+      attribute_list.push(create_attribute("Tier", ticket_tier.name.clone()));
+    }
 
     // Create ticket plugins
     let ticket_plugins = create_ticket_plugins(attribute_list, self.community.key());
@@ -161,12 +383,26 @@ impl<'info> JoinEvent<'info> {
     ];
 
     // we derive the name from the collection but add Ticket + No.
-    let name = format!(
-      "{} #{}",
-      self.event_collection.name,
-      self.event_collection.num_minted + 1
-    );
-    let uri = self.event_collection.uri.clone();
+    // tiered tickets also carry the tier name and artwork.
+    let (name, uri) = match &self.ticket_tier {
+      Some(ticket_tier) => (
+        format!(
+          "{} {} #{}",
+          self.event_collection.name,
+          ticket_tier.name,
+          self.event_collection.num_minted + 1
+        ),
+        ticket_tier.uri.clone(),
+      ),
+      None => (
+        format!(
+          "{} #{}",
+          self.event_collection.name,
+          self.event_collection.num_minted + 1
+        ),
+        self.event_collection.uri.clone(),
+      ),
+    };
     // Create the Ticket
     CreateV2CpiBuilder::new(&self.mpl_core_program.to_account_info())
       .asset(&self.ticket.to_account_info())
@@ -184,100 +420,284 @@ impl<'info> JoinEvent<'info> {
     Ok(())
   }
 
-  pub fn validate_event_version<'a>(&self, remaining_accounts: &[AccountInfo<'a>]) -> Result<()> {
-    let remaining_account_iter = &mut remaining_accounts.iter();
+  /// Remaining accounts of a gate, leaves of the rule tree in prefix order:
+  /// - Token: mint, token account of the attendee
+  /// - Nft: mint, token account of the attendee, metadata
+  /// - Member: member account of the attendee
+  /// - CoreCollection, CoreCreator, CoreAttribute: asset of the attendee
+  /// - Attendance, Allowlist: none
+  ///
+  /// every leaf consumes its accounts, any account (e.g. the system program)
+  /// can be passed for an Nft or Core alternative the attendee does not hold.
+  pub fn validate_event_version<'a, 'b>(
+    &self,
+    event_version: &EventVersion,
+    allowlist_proof: Option<&AllowlistProof>,
+    remaining_account_iter: &mut Iter<'b, AccountInfo<'a>>,
+  ) -> Result<()> {
+    let nodes = event_version.gate_nodes();
+    // Regular events always pass the validation
+    if nodes.is_empty() {
+      return Ok(());
+    }
+    let mut position = 0;
+    self.evaluate_gate_rule(
+      &nodes,
+      &mut position,
+      allowlist_proof,
+      remaining_account_iter,
+    )?
+  }
 
-    match &self.event.event_version {
-      EventVersion::NftGated(nft_data) => {
-        let mut validation_results = vec![];
-        // Check if we have enough remaining accounts
-        if remaining_accounts.len() < 3 {
-          return Err(FoshoErrors::NotEnoughRemainingAccounts.into());
+  /// The outer result fails on malformed rules or accounts,
+  /// the inner result is the verdict of the rule starting at `position`.
+  fn evaluate_gate_rule<'a, 'b>(
+    &self,
+    nodes: &[GateNode],
+    position: &mut usize,
+    allowlist_proof: Option<&AllowlistProof>,
+    remaining_account_iter: &mut Iter<'b, AccountInfo<'a>>,
+  ) -> Result<Result<()>> {
+    let node = nodes.get(*position).ok_or(FoshoErrors::InvalidGateRules)?;
+    *position += 1;
+
+    match node {
+      GateNode::All(count) => {
+        // the first failed operand explains the failure
+        let mut verdict = Ok(());
+        for _ in 0..*count {
+          let operand =
+            self.evaluate_gate_rule(nodes, position, allowlist_proof, remaining_account_iter)?;
+          if verdict.is_ok() {
+            verdict = operand;
+          }
         }
-        let mint_account = next_account_info(remaining_account_iter)?;
-        let mint_account_key = mint_account.key();
-        let ata_mint_account = next_account_info(remaining_account_iter)?;
-        let mint_metadata_account = next_account_info(remaining_account_iter)?;
-
-        assert_is_ata(
-          ata_mint_account,
-          &self.attendee.key(),
-          &mint_account_key,
-          true,
-          &self.token_program.key(),
-        )?;
-        if let Some(verified_creator) = nft_data.verified_creator {
-          validation_results
-            .push(validate_verified_nft_creator(mint_metadata_account, &verified_creator).is_ok());
-        }
-        if let Some(collection_mint) = nft_data.collection_mint {
-          validation_results
-            .push(validate_nft_collection(mint_metadata_account, collection_mint).is_ok());
+        Ok(verdict)
+      }
+      GateNode::Any(count) => {
+        let mut verdict = Err(FoshoErrors::NoGateRuleSatisfied.into());
+        for _ in 0..*count {
+          if self
+            .evaluate_gate_rule(nodes, position, allowlist_proof, remaining_account_iter)?
+            .is_ok()
+          {
+            verdict = Ok(());
+          }
         }
-        if !validation_results.iter().any(|&x| x) {
-          return Err(FoshoErrors::InvalidCollectionDetails.into());
+        Ok(verdict)
+      }
+      GateNode::Not => {
+        match self.evaluate_gate_rule(nodes, position, allowlist_proof, remaining_account_iter)? {
+          Ok(()) => Ok(Err(FoshoErrors::ExcludedByGateRule.into())),
+          Err(_) => Ok(Ok(())),
         }
       }
-      EventVersion::TokenGated(token_data) => {
-        let mut validation_results = vec![];
-        // Check if we have enough remaining accounts
-        if remaining_accounts.len() < 2 {
-          return Err(FoshoErrors::NotEnoughRemainingAccounts.into());
+      GateNode::Token(token_data) => self.evaluate_token_gate(token_data, remaining_account_iter),
+      GateNode::Nft(nft_data) => {
+        let mint_account = next_gate_account(remaining_account_iter)?;
+        let token_account = next_gate_account(remaining_account_iter)?;
+        let metadata_account = next_gate_account(remaining_account_iter)?;
+        Ok(self.evaluate_nft_gate(nft_data, mint_account, token_account, metadata_account))
+      }
+      GateNode::Member => self.evaluate_member_gate(remaining_account_iter),
+      GateNode::Allowlist { merkle_root } => match allowlist_proof {
+        Some(allowlist_proof) if allowlist_proof.verify(&self.attendee.key(), merkle_root) => {
+          Ok(Ok(()))
         }
+        _ => Ok(Err(FoshoErrors::NotAllowlisted.into())),
+      },
+      GateNode::Attendance(attendance_data) => {
+        let attendance_history = &self.attendance_history;
+        let count = match attendance_data.last_events {
+          Some(last_events) => attendance_history.count_before(self.event.nonce, last_events),
+          None => attendance_history.verified_count,
+        };
+        if count >= attendance_data.min_count {
+          Ok(Ok(()))
+        } else {
+          Ok(Err(FoshoErrors::NotEnoughPastAttendances.into()))
+        }
+      }
+      GateNode::CoreCollection(collection_data) => {
+        let asset_account = next_gate_account(remaining_account_iter)?;
+        Ok(
+          load_core_asset(asset_account, &self.attendee.key()).and_then(|asset_data| {
+            validate_core_asset_collection(&asset_data, &collection_data.collection)
+          }),
+        )
+      }
+      GateNode::CoreCreator(creator_data) => {
+        let asset_account = next_gate_account(remaining_account_iter)?;
+        Ok(
+          load_core_asset(asset_account, &self.attendee.key())
+            .and_then(|_| validate_core_asset_creator(asset_account, &creator_data.creator)),
+        )
+      }
+      GateNode::CoreAttribute(attribute_data) => {
+        let asset_account = next_gate_account(remaining_account_iter)?;
+        Ok(
+          load_core_asset(asset_account, &self.attendee.key())
+            .and_then(|asset_data| {
+              validate_core_asset_collection(&asset_data, &attribute_data.collection)
+            })
+            .and_then(|_| {
+              validate_core_asset_attribute(
+                asset_account,
+                &attribute_data.key,
+                &attribute_data.value,
+              )
+            }),
+        )
+      }
+    }
+  }
 
-        let mint_account = next_account_info(remaining_account_iter)?;
-        let mint_account_key = mint_account.key();
-        let ata_mint_account = next_account_info(remaining_account_iter)?;
+  // the token account is derived from the attendee, the mint and the program owning
+  // the mint so it can be negated, a missing token account holds no tokens.
+  fn evaluate_token_gate<'a, 'b>(
+    &self,
+    token_data: &TokenData,
+    remaining_account_iter: &mut Iter<'b, AccountInfo<'a>>,
+  ) -> Result<Result<()>> {
+    let mint_account = next_gate_account(remaining_account_iter)?;
+    let mint_account_key = mint_account.key();
+    let ata_mint_account = next_gate_account(remaining_account_iter)?;
+
+    if let Some(mint) = token_data.mint {
+      require_keys_eq!(mint_account_key, mint, FoshoErrors::InvalidTokenDetails);
+    }
+    let mint_token_program = *mint_account.owner;
+    require!(
+      mint_token_program == TOKEN_PROGRAM_ID || mint_token_program == TOKEN_2022_PROGRAM_ID,
+      FoshoErrors::WrongAccountOwner
+    );
+    let initialized = !ata_mint_account.data_is_empty();
+    assert_is_ata(
+      ata_mint_account,
+      &self.attendee.key(),
+      &mint_account_key,
+      initialized,
+      &mint_token_program,
+    )?;
 
-        assert_is_ata(
-          ata_mint_account,
-          &self.attendee.key(),
-          &mint_account_key,
-          true,
-          &self.token_program.key(),
-        )?;
+    let owned_amount = if initialized {
+      get_spl_token_amount(ata_mint_account)?
+    } else {
+      0
+    };
+    // both the mint and the minimum amount have to match
+    if owned_amount >= token_data.minimum_amount.unwrap_or(1) {
+      Ok(Ok(()))
+    } else {
+      Ok(Err(FoshoErrors::InvalidTokenDetails.into()))
+    }
+  }
 
-        if let Some(mint) = token_data.mint {
-          validation_results.push(mint == mint_account_key);
-        }
-        if let Some(min_amount) = token_data.minimum_amount {
-          let owned_amount = get_spl_token_amount(&ata_mint_account)?;
-          validation_results.push(owned_amount >= min_amount);
-        }
+  fn evaluate_nft_gate(
+    &self,
+    nft_data: &NftData,
+    mint_account: &AccountInfo,
+    ata_mint_account: &AccountInfo,
+    mint_metadata_account: &AccountInfo,
+  ) -> Result<()> {
+    let mint_account_key = mint_account.key();
+    assert_is_ata(
+      ata_mint_account,
+      &self.attendee.key(),
+      &mint_account_key,
+      true,
+      &self.token_program.key(),
+    )?;
+    require_gte!(
+      get_spl_token_amount(ata_mint_account)?,
+      1,
+      FoshoErrors::InvalidTokenDetails
+    );
+    assert_owned_by(mint_metadata_account, &METADATA_PROGRAM_ID)?;
+    let metadata =
+      MetadataAccount::try_deserialize(&mut &mint_metadata_account.try_borrow_data()?[..])?;
+    assert_keys_equal(metadata.mint, mint_account_key)?;
 
-        if !validation_results.iter().any(|&x| x) {
-          return Err(FoshoErrors::InvalidTokenDetails.into());
-        }
-      }
-      _ => {} // Regular events always pass the validation
+    if nft_data.verified_creator.is_none() && nft_data.collection_mint.is_none() {
+      return Err(FoshoErrors::InvalidCollectionDetails.into());
+    }
+    // both the creator and the collection have to match
+    if let Some(verified_creator) = nft_data.verified_creator {
+      validate_verified_nft_creator(mint_metadata_account, &verified_creator)?;
+    }
+    if let Some(collection_mint) = nft_data.collection_mint {
+      validate_nft_collection(mint_metadata_account, collection_mint)?;
     }
-
     Ok(())
   }
+
+  // the member account is derived from the attendee so it can be negated,
+  // a missing member account is not an active member.
+  fn evaluate_member_gate<'a, 'b>(
+    &self,
+    remaining_account_iter: &mut Iter<'b, AccountInfo<'a>>,
+  ) -> Result<Result<()>> {
+    let member_account = next_gate_account(remaining_account_iter)?;
+    let (member_key, _) = Pubkey::find_program_address(
+      &[
+        MEMBER_PRE_SEED,
+        self.community.key().as_ref(),
+        self.attendee.key().as_ref(),
+      ],
+      &crate::ID,
+    );
+    require_keys_eq!(member_account.key(), member_key, FoshoErrors::NotAMember);
+
+    if *member_account.owner != crate::ID {
+      return Ok(Err(FoshoErrors::NotAMember.into()));
+    }
+    let member = Member::try_deserialize(&mut &member_account.try_borrow_data()?[..])?;
+    if member.status == MemberStatus::Active {
+      Ok(Ok(()))
+    } else {
+      Ok(Err(FoshoErrors::NotAMember.into()))
+    }
+  }
 }
 
-pub fn join_event_handler(ctx: Context<JoinEvent>) -> Result<()> {
+fn next_gate_account<'a, 'b>(
+  remaining_account_iter: &mut Iter<'b, AccountInfo<'a>>,
+) -> Result<&'b AccountInfo<'a>> {
+  remaining_account_iter
+    .next()
+    .ok_or(FoshoErrors::NotEnoughRemainingAccounts.into())
+}
+
+pub fn join_event_handler(
+  ctx: Context<JoinEvent>,
+  allowlist_proof: Option<AllowlistProof>,
+  invitation: Option<Invitation>,
+) -> Result<()> {
   let event = &ctx.accounts.event;
 
   if event.authority_must_sign {
-    let is_community_authority =
-      ctx.accounts.event_authority.key() == ctx.accounts.community.authority;
-    if !is_community_authority {
-      require!(
-        event
-          .event_authorities
-          .contains(&ctx.accounts.event_authority.key()),
-        FoshoErrors::InvalidEventAuthority
-      );
+    event.assert_role(
+      &ctx.accounts.event_authority.key(),
+      &ctx.accounts.community.authority,
+      ROLE_APPROVER,
+    )?;
+    if !ctx.accounts.event_authority.is_signer {
+      let invitation = invitation
+        .as_ref()
+        .ok_or(FoshoErrors::EventAuthorityMustSign)?;
+      let invitation_bump = ctx.bumps.invitation_record.unwrap_or_default();
+      ctx.accounts.use_invitation(invitation, invitation_bump)?;
     }
-    require!(
-      ctx.accounts.event_authority.is_signer,
-      FoshoErrors::EventAuthorityMustSign
-    );
   }
+  let event = &ctx.accounts.event;
+
+  event.assert_allows(EventAction::Join)?;
 
-  if event.is_cancelled {
-    return Err(FoshoErrors::EventCancelled.into());
+  if event.tiers_count.gt(&0) {
+    require!(
+      ctx.accounts.ticket_tier.is_some(),
+      FoshoErrors::TicketTierRequired
+    );
   }
 
   // handled by event collection
@@ -286,10 +706,36 @@ pub fn join_event_handler(ctx: Context<JoinEvent>) -> Result<()> {
   // require_gte!(event.registration_end_time, current_time, FoshoErrors::RegistrationTimeExpired);
   // require_gt!(event.max_attendees, event.current_attendees, FoshoErrors::MaxAttendeesAlreadyJoined);
 
+  // the entry of a verified allowlist proof restricts the tier and discounts the fee
+  let allowlist_entry = ctx
+    .accounts
+    .verified_allowlist_proof(allowlist_proof.as_ref());
+  if let Some(tier) = allowlist_entry.and_then(|entry| entry.tier) {
+    let ticket_tier = ctx
+      .accounts
+      .ticket_tier
+      .as_ref()
+      .ok_or(FoshoErrors::TicketTierRequired)?;
+    require_eq!(ticket_tier.index, tier, FoshoErrors::InvalidTicketTier);
+  }
+
   ctx.accounts.create_event_ticket(ctx.bumps.ticket)?;
 
-  if event.commitment_fee.gt(&0) {
-    transfer(ctx.accounts.transfer_commitment_fee(), event.commitment_fee)?;
+  let mut commitment_fee = ctx.accounts.commitment_fee();
+  if let Some(entry) = allowlist_entry {
+    let discount = (commitment_fee as u128)
+      .checked_mul(entry.discount_bps as u128)
+      .ok_or(FoshoErrors::NumericalOverflow)?
+      / BPS_DENOMINATOR as u128;
+    commitment_fee = commitment_fee.checked_sub(discount as u64).unwrap();
+  }
+  if commitment_fee.gt(&0) {
+    if ctx.accounts.event.fee_mint.is_some() {
+      commitment_fee = ctx.accounts.deposit_commitment_fee_tokens(commitment_fee)?;
+    } else {
+      transfer(ctx.accounts.transfer_commitment_fee(), commitment_fee)?;
+    }
+    ctx.accounts.vault.record_deposit(commitment_fee)?;
   }
 
   // handled by event collection
@@ -297,19 +743,60 @@ pub fn join_event_handler(ctx: Context<JoinEvent>) -> Result<()> {
   // event.current_attendees = event.current_attendees.checked_add(1).unwrap();
 
   // data used for the claiming of rewards
+  let reward_amount = ctx.accounts.reward_amount();
+  let tier = ctx.accounts.ticket_tier.as_ref().map(|tier| tier.index);
   let attendee_record = &mut ctx.accounts.attendee_record;
   attendee_record.owner = ctx.accounts.attendee.key();
   attendee_record.event = ctx.accounts.event.key();
   attendee_record.status = AttendeeStatus::Pending;
   attendee_record.bump = ctx.bumps.attendee_record;
+  attendee_record.commitment_fee = commitment_fee;
+  attendee_record.reward_amount = reward_amount;
+  attendee_record.tier = tier;
+  attendee_record.sessions_attended = 0;
+
+  let event = &mut ctx.accounts.event;
+  event.attendees_count = event.attendees_count.checked_add(1).unwrap();
+
+  // every registered attendee has to be able to claim their reward
+  if reward_amount.gt(&0) {
+    event.reward_liability = event.reward_liability.checked_add(reward_amount).unwrap();
+    let reward_account = ctx
+      .accounts
+      .reward_account
+      .as_ref()
+      .ok_or(FoshoErrors::AccountNotProvided)?;
+    require_gte!(
+      reward_account.amount,
+      event.reward_liability,
+      FoshoErrors::RewardsNotFunded
+    );
+  }
 
-  match event.event_version {
-    EventVersion::Regular => {}
-    _ => {
-      ctx
-        .accounts
-        .validate_event_version(&ctx.remaining_accounts)?;
-    }
+  if let Some(ticket_tier) = ctx.accounts.ticket_tier.as_mut() {
+    ticket_tier.attendees_count = ticket_tier.attendees_count.checked_add(1).unwrap();
+  }
+
+  // the attendance history is created on the first join in the community
+  ctx.accounts.attendance_history.init_if_new(
+    ctx.accounts.event.community,
+    ctx.accounts.attendee.key(),
+    ctx.bumps.attendance_history,
+  );
+
+  // remaining accounts of the event gate come first, followed by the ones of the tier gate
+  let remaining_account_iter = &mut ctx.remaining_accounts.iter();
+  ctx.accounts.validate_event_version(
+    &ctx.accounts.event.event_version,
+    allowlist_proof.as_ref(),
+    remaining_account_iter,
+  )?;
+  if let Some(ticket_tier) = &ctx.accounts.ticket_tier {
+    ctx.accounts.validate_event_version(
+      &ticket_tier.event_version,
+      allowlist_proof.as_ref(),
+      remaining_account_iter,
+    )?;
   }
   Ok(())
 }
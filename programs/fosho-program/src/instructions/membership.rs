@@ -0,0 +1,260 @@
+use crate::{constant::*, error::FoshoErrors, state::*};
+use anchor_lang::{
+  prelude::*,
+  system_program::{transfer, Transfer},
+};
+
+#[derive(Accounts)]
+pub struct InviteMember<'info> {
+  /// removed members can be invited again
+  #[account(
+    init_if_needed,
+    seeds = [
+      MEMBER_PRE_SEED,
+      community.key().as_ref(),
+      wallet.key().as_ref()
+    ],
+    bump,
+    payer = authority,
+    space = 8 + Member::INIT_SPACE
+  )]
+  pub member: Account<'info, Member>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+    has_one = authority
+  )]
+  pub community: Account<'info, Community>,
+  /// CHECK: any wallet can be invited
+  pub wallet: UncheckedAccount<'info>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptInvitation<'info> {
+  #[account(
+    mut,
+    seeds = [
+      MEMBER_PRE_SEED,
+      community.key().as_ref(),
+      wallet.key().as_ref()
+    ],
+    bump = member.bump,
+    has_one = community,
+    has_one = wallet,
+  )]
+  pub member: Account<'info, Member>,
+  #[account(
+    mut,
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Account<'info, Community>,
+  pub wallet: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct JoinCommunity<'info> {
+  #[account(
+    init,
+    seeds = [
+      MEMBER_PRE_SEED,
+      community.key().as_ref(),
+      wallet.key().as_ref()
+    ],
+    bump,
+    payer = wallet,
+    space = 8 + Member::INIT_SPACE
+  )]
+  pub member: Account<'info, Member>,
+  #[account(
+    mut,
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Account<'info, Community>,
+  /// receives the membership fee
+  /// CHECK: checked against the community authority
+  #[account(mut, address = community.authority @ FoshoErrors::PublicKeyMismatch)]
+  pub community_authority: UncheckedAccount<'info>,
+  #[account(mut)]
+  pub wallet: Signer<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+/// signed by the community authority or by an active moderator
+#[derive(Accounts)]
+pub struct ManageMember<'info> {
+  #[account(
+    mut,
+    seeds = [
+      MEMBER_PRE_SEED,
+      community.key().as_ref(),
+      member.wallet.as_ref()
+    ],
+    bump = member.bump,
+    has_one = community,
+    has_one = wallet,
+  )]
+  pub member: Account<'info, Member>,
+  #[account(
+    mut,
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Account<'info, Community>,
+  /// member account of the signer, required when it is not the community authority
+  #[account(
+    seeds = [
+      MEMBER_PRE_SEED,
+      community.key().as_ref(),
+      authority.key().as_ref()
+    ],
+    bump = moderator.bump,
+  )]
+  pub moderator: Option<Account<'info, Member>>,
+  /// receives the escrowed membership fee on approval
+  /// CHECK: checked against the community authority
+  #[account(mut, address = community.authority @ FoshoErrors::PublicKeyMismatch)]
+  pub community_authority: UncheckedAccount<'info>,
+  /// refunded the escrowed membership fee on rejection
+  /// CHECK: checked against the member wallet
+  #[account(mut)]
+  pub wallet: UncheckedAccount<'info>,
+  pub authority: Signer<'info>,
+}
+
+impl<'info> ManageMember<'info> {
+  // the community authority manages every member, moderators only review pending applications
+  fn assert_manager(&self) -> Result<()> {
+    if self.authority.key() == self.community.authority {
+      return Ok(());
+    }
+    let moderator = self.moderator.as_ref().ok_or(FoshoErrors::NotAModerator)?;
+    require!(
+      moderator.role == MemberRole::Moderator
+        && moderator.status == MemberStatus::Active
+        && self.member.status == MemberStatus::Pending,
+      FoshoErrors::NotAModerator
+    );
+    Ok(())
+  }
+
+  fn release_fee(&mut self, receiver: AccountInfo<'info>) -> Result<()> {
+    let escrowed_fee = self.member.escrowed_fee;
+    if escrowed_fee.gt(&0) {
+      self.member.escrowed_fee = 0;
+      self.member.sub_lamports(escrowed_fee)?;
+      receiver.add_lamports(escrowed_fee)?;
+    }
+    Ok(())
+  }
+}
+
+// invited members do not pay the membership fee
+pub fn invite_member_handler(ctx: Context<InviteMember>, role: MemberRole) -> Result<()> {
+  let member = &mut ctx.accounts.member;
+  require!(
+    member.community == Pubkey::default() || member.status == MemberStatus::Removed,
+    FoshoErrors::InvalidMemberStatus
+  );
+  member.community = ctx.accounts.community.key();
+  member.wallet = ctx.accounts.wallet.key();
+  member.bump = ctx.bumps.member;
+  member.joined_at = 0;
+  member.role = role;
+  member.status = MemberStatus::Invited;
+  member.escrowed_fee = 0;
+  Ok(())
+}
+
+pub fn accept_invitation_handler(ctx: Context<AcceptInvitation>) -> Result<()> {
+  let member = &mut ctx.accounts.member;
+  require!(
+    member.status == MemberStatus::Invited,
+    FoshoErrors::InvalidMemberStatus
+  );
+  member.activate(&mut ctx.accounts.community)
+}
+
+// the membership fee is held by the member account until the application is reviewed
+pub fn join_community_handler(ctx: Context<JoinCommunity>) -> Result<()> {
+  let requires_approval = ctx.accounts.community.membership_requires_approval;
+  let membership_fee = ctx.accounts.community.membership_fee;
+  if membership_fee.gt(&0) {
+    let to = if requires_approval {
+      ctx.accounts.member.to_account_info()
+    } else {
+      ctx.accounts.community_authority.to_account_info()
+    };
+    let cpi_accounts = Transfer {
+      from: ctx.accounts.wallet.to_account_info(),
+      to,
+    };
+    transfer(
+      CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts),
+      membership_fee,
+    )?;
+  }
+
+  let member = &mut ctx.accounts.member;
+  member.community = ctx.accounts.community.key();
+  member.wallet = ctx.accounts.wallet.key();
+  member.bump = ctx.bumps.member;
+  member.joined_at = 0;
+  member.role = MemberRole::Member;
+  member.status = MemberStatus::Pending;
+  member.escrowed_fee = 0;
+
+  if requires_approval {
+    member.escrowed_fee = membership_fee;
+  } else {
+    member.activate(&mut ctx.accounts.community)?;
+  }
+  Ok(())
+}
+
+pub fn approve_member_handler(ctx: Context<ManageMember>) -> Result<()> {
+  ctx.accounts.assert_manager()?;
+  require!(
+    ctx.accounts.member.status == MemberStatus::Pending,
+    FoshoErrors::InvalidMemberStatus
+  );
+  let community_authority = ctx.accounts.community_authority.to_account_info();
+  ctx.accounts.release_fee(community_authority)?;
+  ctx.accounts.member.activate(&mut ctx.accounts.community)
+}
+
+// the member account is kept, a removed wallet can only come back with an invitation
+pub fn remove_member_handler(ctx: Context<ManageMember>) -> Result<()> {
+  ctx.accounts.assert_manager()?;
+  match ctx.accounts.member.status {
+    MemberStatus::Removed => return err!(FoshoErrors::InvalidMemberStatus),
+    MemberStatus::Active => {
+      let community = &mut ctx.accounts.community;
+      community.members_count = community.members_count.checked_sub(1).unwrap();
+    }
+    // rejected applicants get their membership fee back
+    MemberStatus::Pending => {
+      let wallet = ctx.accounts.wallet.to_account_info();
+      ctx.accounts.release_fee(wallet)?;
+    }
+    MemberStatus::Invited => {}
+  }
+  ctx.accounts.member.status = MemberStatus::Removed;
+  Ok(())
+}
@@ -18,3 +18,63 @@ mod cancel_event;
 
 pub use reject_attendance::*;
 mod reject_attendance;
+
+pub use update_event::*;
+mod update_event;
+
+pub use event_status::*;
+mod event_status;
+
+pub use close_event::*;
+mod close_event;
+
+pub use unjoin_event::*;
+mod unjoin_event;
+
+pub use create_ticket_tier::*;
+mod create_ticket_tier;
+
+pub use reconcile_vault::*;
+mod reconcile_vault;
+
+pub use fund_rewards::*;
+mod fund_rewards;
+
+pub use withdraw_unallocated_rewards::*;
+mod withdraw_unallocated_rewards;
+
+pub use event_authorities::*;
+mod event_authorities;
+
+pub use community_authority::*;
+mod community_authority;
+
+pub use update_community::*;
+mod update_community;
+
+pub use governance::*;
+mod governance;
+
+pub use membership::*;
+mod membership;
+
+pub use set_allowlist_root::*;
+mod set_allowlist_root;
+
+pub use batch_scan::*;
+mod batch_scan;
+
+pub use create_session::*;
+mod create_session;
+
+pub use verify_session::*;
+mod verify_session;
+
+pub use check_out_attendee::*;
+mod check_out_attendee;
+
+pub use amend_attendance::*;
+mod amend_attendance;
+
+pub use appeal_rejection::*;
+mod appeal_rejection;
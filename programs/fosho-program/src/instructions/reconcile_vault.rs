@@ -0,0 +1,78 @@
+use crate::{constant::*, error::FoshoErrors, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct ReconcileVault<'info> {
+  #[account(
+    seeds = [
+      EVENT_PRE_SEED,
+      event.community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
+  /// the fee accounts are required if the commitment fee is paid in tokens
+  #[account(
+    mint::token_program = fee_token_program,
+  )]
+  pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+  #[account(
+    associated_token::mint = fee_mint,
+    associated_token::authority = vault,
+    associated_token::token_program = fee_token_program
+  )]
+  pub fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  pub fee_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> ReconcileVault<'info> {
+  /// fees held by the vault, the rent of the vault is not part of it
+  pub fn vault_balance(&self) -> Result<u64> {
+    if let Some(fee_mint) = self.event.fee_mint {
+      if self.fee_mint.is_none() || self.fee_account.is_none() {
+        return Err(FoshoErrors::AccountNotProvided.into());
+      }
+      require_keys_eq!(
+        self.fee_mint.as_ref().unwrap().key(),
+        fee_mint,
+        FoshoErrors::PublicKeyMismatch
+      );
+      return Ok(self.fee_account.as_ref().unwrap().amount);
+    }
+
+    let vault_info = self.vault.to_account_info();
+    let rent = Rent::get()?.minimum_balance(vault_info.data_len());
+    vault_info
+      .lamports()
+      .checked_sub(rent)
+      .ok_or(FoshoErrors::NumericalOverflow.into())
+  }
+}
+
+// permissionless, fails if the vault does not hold exactly the fees still owed
+pub fn reconcile_vault_handler(ctx: Context<ReconcileVault>) -> Result<()> {
+  let balance = ctx.accounts.vault_balance()?;
+  let vault = &ctx.accounts.vault;
+
+  msg!(
+    "deposited: {}, refunded: {}, forfeited: {}, balance: {}",
+    vault.total_deposited,
+    vault.total_refunded,
+    vault.total_forfeited,
+    balance
+  );
+
+  vault.assert_reconciled(balance)
+}
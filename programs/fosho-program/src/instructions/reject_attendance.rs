@@ -1,12 +1,14 @@
-use crate::{constant::*, error::FoshoErrors, state::*, utils::check_if_already_scanned};
+use crate::{
+  constant::*,
+  error::FoshoErrors,
+  state::*,
+  utils::{check_if_already_scanned, write_ticket_scan, TicketCpiAccounts},
+};
 use anchor_lang::prelude::*;
 
 use mpl_core::{
   accounts::{BaseAssetV1, BaseCollectionV1},
-  instructions::{UpdatePluginV1CpiBuilder, WriteExternalPluginAdapterDataV1CpiBuilder},
-  types::{
-    ExternalPluginAdapterKey, PermanentFreezeDelegate, Plugin, PluginAuthority, UpdateAuthority,
-  },
+  types::UpdateAuthority,
   ID as MPL_CORE_ID,
 };
 
@@ -80,41 +82,17 @@ impl<'info> RejectAttendee<'info> {
     // check if community authority scanned
     check_if_already_scanned(self.ticket.to_account_info(), &self.community.key())?;
 
-    let data: Vec<u8> = "Rejected".as_bytes().to_vec();
-
-    let signer_seeds = &[
-      COMMUNITY_PRE_SEED.as_ref(),
-      self.community.seed.as_ref(),
-      &[self.community.bump],
-    ];
-
-    // The event authority is the `signer` of this instruction.
-    WriteExternalPluginAdapterDataV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
-      .asset(&self.ticket.to_account_info())
-      .collection(Some(&self.event_collection.to_account_info()))
-      .payer(&self.event_authority.to_account_info())
-      .authority(Some(&self.community.to_account_info()))
-      .system_program(&self.system_program.to_account_info())
-      .key(ExternalPluginAdapterKey::AppData(
-        PluginAuthority::Address {
-          address: self.community.key(),
-        },
-      ))
-      .data(data)
-      .invoke_signed(&[signer_seeds])?;
-
-    UpdatePluginV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
-      .asset(&self.ticket.to_account_info())
-      .collection(Some(&self.event_collection.to_account_info()))
-      .payer(&self.event_authority.to_account_info())
-      .authority(Some(&self.community.to_account_info()))
-      .system_program(&self.system_program.to_account_info())
-      .plugin(Plugin::PermanentFreezeDelegate(PermanentFreezeDelegate {
-        frozen: true,
-      }))
-      .invoke_signed(&[signer_seeds])?;
-
-    Ok(())
+    write_ticket_scan(
+      &TicketCpiAccounts {
+        mpl_core_program: &self.mpl_core_program.to_account_info(),
+        ticket: &self.ticket.to_account_info(),
+        event_collection: &self.event_collection.to_account_info(),
+        payer: &self.event_authority.to_account_info(),
+        community: &self.community,
+        system_program: &self.system_program.to_account_info(),
+      },
+      "Rejected",
+    )
   }
 }
 
@@ -122,6 +100,8 @@ pub fn reject_attendee_handler(ctx: Context<RejectAttendee>) -> Result<()> {
   let attendee_record = &mut ctx.accounts.attendee_record;
   let event = &ctx.accounts.event;
 
+  event.assert_allows(EventAction::Reject)?;
+
   match attendee_record.status {
     AttendeeStatus::Pending => {}
     AttendeeStatus::Claimed => {
@@ -130,21 +110,16 @@ pub fn reject_attendee_handler(ctx: Context<RejectAttendee>) -> Result<()> {
     AttendeeStatus::Rejected => {
       return Err(FoshoErrors::AlreadyScanned.into());
     }
-    AttendeeStatus::Verified => {
+    AttendeeStatus::Verified | AttendeeStatus::PartiallyAttended => {
       return Err(FoshoErrors::AlreadyScanned.into());
     }
   }
 
-  let is_community_authority =
-    ctx.accounts.event_authority.key() == ctx.accounts.community.authority;
-  if !is_community_authority {
-    require!(
-      event
-        .event_authorities
-        .contains(&ctx.accounts.event_authority.key()),
-      FoshoErrors::InvalidEventAuthority
-    );
-  }
+  event.assert_role(
+    &ctx.accounts.event_authority.key(),
+    &ctx.accounts.community.authority,
+    ROLE_APPROVER,
+  )?;
   attendee_record.status = AttendeeStatus::Rejected;
 
   ctx.accounts.scan_ticket()?;
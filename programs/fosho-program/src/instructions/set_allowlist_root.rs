@@ -0,0 +1,59 @@
+use crate::{constant::*, error::FoshoErrors, state::*};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SetAllowlistRoot<'info> {
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  /// the root of the tier gate is replaced instead of the event gate if provided
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      TICKET_TIER_SUFFIX_SEED,
+      &[ticket_tier.index],
+    ],
+    bump = ticket_tier.bump,
+    has_one = event @ FoshoErrors::InvalidTicketTier,
+  )]
+  pub ticket_tier: Option<Box<Account<'info, TicketTier>>>,
+  pub authority: Signer<'info>,
+}
+
+// entries removed from the list cannot join anymore, attendees that already joined stay
+pub fn set_allowlist_root_handler(
+  ctx: Context<SetAllowlistRoot>,
+  merkle_root: [u8; 32],
+) -> Result<()> {
+  let event = &mut ctx.accounts.event;
+
+  event.assert_allows(EventAction::Update)?;
+  event.assert_role(
+    &ctx.accounts.authority.key(),
+    &ctx.accounts.community.authority,
+    ROLE_APPROVER,
+  )?;
+
+  match ctx.accounts.ticket_tier.as_mut() {
+    Some(ticket_tier) => ticket_tier.event_version.set_allowlist_root(merkle_root),
+    None => event.event_version.set_allowlist_root(merkle_root),
+  }
+}
@@ -0,0 +1,341 @@
+use crate::{
+  constant::*,
+  error::FoshoErrors,
+  state::*,
+  utils::{burn_from_vault, transfer_from_vault},
+};
+use anchor_lang::{prelude::*, solana_program::incinerator};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use mpl_core::{
+  accounts::{BaseAssetV1, BaseCollectionV1},
+  instructions::BurnV1CpiBuilder,
+  types::UpdateAuthority,
+  ID as MPL_CORE_ID,
+};
+
+#[derive(Accounts)]
+pub struct UnjoinEvent<'info> {
+  #[account(
+    mut,
+    seeds = [
+      ATTENDEE_PRE_SEED,
+      event.key().as_ref(),
+      attendee.key().as_ref()
+    ],
+    bump = attendee_record.bump,
+    has_one = event,
+    constraint = attendee_record.owner == attendee.key() @ FoshoErrors::InvalidClaimer,
+    close = attendee,
+  )]
+  pub attendee_record: Box<Account<'info, Attendee>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED,
+        event.key().as_ref(),
+        EVENT_COLLECTION_SUFFIX_SEED,
+      ],
+      bump,
+      constraint = event_collection.update_authority == community.key(),
+  )]
+  pub event_collection: Box<Account<'info, BaseCollectionV1>>,
+  /// required if the attendee joined with a ticket tier
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      TICKET_TIER_SUFFIX_SEED,
+      &[ticket_tier.index],
+    ],
+    bump = ticket_tier.bump,
+    has_one = event @ FoshoErrors::InvalidTicketTier,
+  )]
+  pub ticket_tier: Option<Box<Account<'info, TicketTier>>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED,
+        event.key().as_ref(),
+        attendee.key().as_ref(),
+        TICKET_SUFFIX_SEED,
+      ],
+      bump,
+      constraint = ticket.owner == attendee.key(),
+      constraint = ticket.update_authority == UpdateAuthority::Collection(event_collection.key()),
+  )]
+  pub ticket: Box<Account<'info, BaseAssetV1>>,
+  #[account(mut)]
+  pub attendee: Signer<'info>,
+  pub system_program: Program<'info, System>,
+  /// CHECK: This is checked by the address constraint
+  #[account(address = MPL_CORE_ID)]
+  pub mpl_core_program: UncheckedAccount<'info>,
+  /// the fee accounts are required if the commitment fee is paid in tokens
+  #[account(
+    mut,
+    mint::token_program = fee_token_program,
+  )]
+  pub fee_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = vault,
+    associated_token::token_program = fee_token_program
+  )]
+  pub fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = attendee,
+    associated_token::token_program = fee_token_program
+  )]
+  pub attendee_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  /// receives the part of the fee that is not refunded with the ToOrganizer forfeit policy
+  /// CHECK: checked against the community authority
+  #[account(mut, address = community.authority @ FoshoErrors::PublicKeyMismatch)]
+  pub community_authority: UncheckedAccount<'info>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = community_authority,
+    associated_token::token_program = fee_token_program
+  )]
+  pub community_authority_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+  pub fee_token_program: Option<Interface<'info, TokenInterface>>,
+  /// treasury of the Treasury forfeit policy, or the incinerator to burn SOL fees
+  /// CHECK: checked against the forfeit policy of the event
+  #[account(mut)]
+  pub forfeit_receiver: Option<UncheckedAccount<'info>>,
+  #[account(
+    mut,
+    associated_token::mint = fee_mint,
+    associated_token::authority = forfeit_receiver,
+    associated_token::token_program = fee_token_program
+  )]
+  pub forfeit_receiver_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+impl<'info> UnjoinEvent<'info> {
+  // the community is the permanent burn delegate of every ticket.
+  // the ticket address cannot be reused, so the wallet cannot join again.
+  pub fn burn_ticket(&self) -> Result<()> {
+    let signer_seeds = &[
+      COMMUNITY_PRE_SEED,
+      self.community.seed.as_ref(),
+      &[self.community.bump],
+    ];
+
+    BurnV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
+      .asset(&self.ticket.to_account_info())
+      .collection(Some(&self.event_collection.to_account_info()))
+      .payer(&self.attendee.to_account_info())
+      .authority(Some(&self.community.to_account_info()))
+      .system_program(Some(&self.system_program.to_account_info()))
+      .invoke_signed(&[signer_seeds])?;
+
+    Ok(())
+  }
+
+  /// pays out of the vault, in SOL or in the fee token of the event
+  fn pay_commitment_fee(
+    &self,
+    receiver: AccountInfo<'info>,
+    receiver_fee_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    amount: u64,
+  ) -> Result<()> {
+    if self.event.fee_mint.is_none() {
+      self.vault.sub_lamports(amount)?;
+      receiver.add_lamports(amount)?;
+      return Ok(());
+    }
+
+    if self.fee_mint.is_none()
+      || self.fee_account.is_none()
+      || receiver_fee_account.is_none()
+      || self.fee_token_program.is_none()
+    {
+      return Err(FoshoErrors::AccountNotProvided.into());
+    }
+    let fee_mint = self.fee_mint.as_ref().unwrap();
+    require!(
+      self.event.fee_mint == Some(fee_mint.key()),
+      FoshoErrors::PublicKeyMismatch
+    );
+
+    transfer_from_vault(
+      &self.vault,
+      self.fee_account.as_ref().unwrap().to_account_info(),
+      receiver_fee_account.unwrap().to_account_info(),
+      fee_mint,
+      self.fee_token_program.as_ref().unwrap().to_account_info(),
+      amount,
+    )
+  }
+
+  pub fn refund_commitment_fee(&self, refund: u64) -> Result<()> {
+    self.pay_commitment_fee(
+      self.attendee.to_account_info(),
+      self.attendee_fee_account.as_deref(),
+      refund,
+    )
+  }
+
+  fn pay_forfeit_receiver(&self, expected: Pubkey, forfeit: u64) -> Result<()> {
+    let forfeit_receiver = self
+      .forfeit_receiver
+      .as_ref()
+      .ok_or(FoshoErrors::AccountNotProvided)?;
+    require_keys_eq!(
+      forfeit_receiver.key(),
+      expected,
+      FoshoErrors::InvalidForfeitReceiver
+    );
+
+    self.pay_commitment_fee(
+      forfeit_receiver.to_account_info(),
+      self.forfeit_receiver_fee_account.as_deref(),
+      forfeit,
+    )
+  }
+
+  fn burn_commitment_fee(&self, forfeit: u64) -> Result<()> {
+    if self.fee_mint.is_none() || self.fee_account.is_none() || self.fee_token_program.is_none() {
+      return Err(FoshoErrors::AccountNotProvided.into());
+    }
+    let fee_mint = self.fee_mint.as_ref().unwrap();
+    require!(
+      self.event.fee_mint == Some(fee_mint.key()),
+      FoshoErrors::PublicKeyMismatch
+    );
+
+    burn_from_vault(
+      &self.vault,
+      self.fee_account.as_ref().unwrap().to_account_info(),
+      fee_mint,
+      self.fee_token_program.as_ref().unwrap().to_account_info(),
+      forfeit,
+    )
+  }
+
+  /// applies the forfeit policy of the event to the part of the fee that is not refunded
+  pub fn forfeit_commitment_fee(&mut self, forfeit: u64) -> Result<()> {
+    match self.event.forfeit_policy.clone() {
+      // stays in the vault and joins the forfeit pool on settlement
+      ForfeitPolicy::ProRata => return Ok(()),
+      ForfeitPolicy::ToOrganizer => {
+        self.pay_commitment_fee(
+          self.community_authority.to_account_info(),
+          self.community_authority_fee_account.as_deref(),
+          forfeit,
+        )?;
+      }
+      ForfeitPolicy::Treasury { address } => {
+        self.pay_forfeit_receiver(address, forfeit)?;
+      }
+      ForfeitPolicy::Burn => {
+        if self.event.fee_mint.is_some() {
+          self.burn_commitment_fee(forfeit)?;
+        } else {
+          self.pay_forfeit_receiver(incinerator::ID, forfeit)?;
+        }
+      }
+    }
+
+    self.vault.record_forfeit(forfeit)
+  }
+}
+
+pub fn unjoin_event_handler(ctx: Context<UnjoinEvent>) -> Result<()> {
+  let event = &ctx.accounts.event;
+
+  event.assert_allows(EventAction::Unjoin)?;
+
+  match ctx.accounts.attendee_record.status {
+    // a checked-in attendee is still pending until the check-out
+    AttendeeStatus::Pending if ctx.accounts.attendee_record.checked_in_at.is_some() => {
+      return Err(FoshoErrors::AlreadyScanned.into());
+    }
+    // same for an attendee scanned at some sessions of the event
+    AttendeeStatus::Pending if ctx.accounts.attendee_record.sessions_attended != 0 => {
+      return Err(FoshoErrors::AlreadyScanned.into());
+    }
+    AttendeeStatus::Pending => {}
+    AttendeeStatus::Claimed => {
+      return Err(FoshoErrors::AlreadyClaimed.into());
+    }
+    AttendeeStatus::Rejected | AttendeeStatus::Verified | AttendeeStatus::PartiallyAttended => {
+      return Err(FoshoErrors::AlreadyScanned.into());
+    }
+  }
+
+  // the part of the fee that is not refunded follows the forfeit policy
+  let commitment_fee = ctx.accounts.attendee_record.commitment_fee;
+  let refund = event.refund_policy.refund_amount(
+    commitment_fee,
+    event.schedule.starts_at,
+    Clock::get()?.unix_timestamp,
+  )?;
+  let forfeit = commitment_fee.checked_sub(refund).unwrap();
+
+  ctx.accounts.burn_ticket()?;
+
+  if refund.gt(&0) {
+    ctx.accounts.refund_commitment_fee(refund)?;
+    ctx.accounts.vault.record_refund(refund)?;
+  }
+  if forfeit.gt(&0) {
+    ctx.accounts.forfeit_commitment_fee(forfeit)?;
+  }
+
+  // frees the seat, the attendee record is closed
+  let event = &mut ctx.accounts.event;
+  event.attendees_count = event.attendees_count.checked_sub(1).unwrap();
+  event.reward_liability = event
+    .reward_liability
+    .checked_sub(ctx.accounts.attendee_record.reward_amount)
+    .unwrap();
+
+  if let Some(tier) = ctx.accounts.attendee_record.tier {
+    let ticket_tier = ctx
+      .accounts
+      .ticket_tier
+      .as_mut()
+      .ok_or(FoshoErrors::TicketTierRequired)?;
+    require_eq!(ticket_tier.index, tier, FoshoErrors::InvalidTicketTier);
+    ticket_tier.attendees_count = ticket_tier.attendees_count.checked_sub(1).unwrap();
+  }
+
+  Ok(())
+}
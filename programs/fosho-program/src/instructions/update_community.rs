@@ -0,0 +1,56 @@
+use crate::{constant::*, state::*};
+use anchor_lang::prelude::*;
+
+/// Every field is optional, only the provided values are changed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateCommunityArgs {
+  pub name: Option<String>,
+  pub uri: Option<String>,
+  pub logo: Option<String>,
+  pub social_links: Option<Vec<String>>,
+  pub membership_fee: Option<u64>,
+  pub membership_requires_approval: Option<bool>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCommunity<'info> {
+  #[account(
+    mut,
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+    has_one = authority
+  )]
+  pub community: Account<'info, Community>,
+  pub authority: Signer<'info>,
+}
+
+pub fn update_community_handler(
+  ctx: Context<UpdateCommunity>,
+  args: UpdateCommunityArgs,
+) -> Result<()> {
+  let community = &mut ctx.accounts.community;
+
+  if let Some(name) = args.name {
+    community.name = name;
+  }
+  if let Some(uri) = args.uri {
+    community.uri = uri;
+  }
+  if let Some(logo) = args.logo {
+    community.logo = logo;
+  }
+  if let Some(social_links) = args.social_links {
+    community.social_links = social_links;
+  }
+  if let Some(membership_fee) = args.membership_fee {
+    community.membership_fee = membership_fee;
+  }
+  if let Some(membership_requires_approval) = args.membership_requires_approval {
+    community.membership_requires_approval = membership_requires_approval;
+  }
+
+  community.validate()
+}
@@ -0,0 +1,283 @@
+use crate::{
+  constant::*,
+  error::FoshoErrors,
+  state::*,
+  utils::{upsert_attribute, validate_event_start_time},
+};
+use anchor_lang::prelude::*;
+
+use mpl_core::{
+  accounts::BaseCollectionV1,
+  fetch_plugin,
+  instructions::{UpdateCollectionPluginV1CpiBuilder, UpdateCollectionV1CpiBuilder},
+  types::{Attributes, Plugin, PluginType},
+  ID as MPL_CORE_ID,
+};
+
+/// Every field is optional, only the provided values are changed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateEventArgs {
+  pub name: Option<String>,
+  pub uri: Option<String>,
+  pub event_type: Option<EventType>,
+  pub organizer: Option<String>,
+  pub commitment_fee: Option<u64>,
+  pub event_starts_at: Option<i64>,
+  pub event_ends_at: Option<i64>,
+  pub registration_starts_at: Option<i64>,
+  pub registration_ends_at: Option<i64>,
+  pub capacity: Option<u64>,
+  pub location: Option<String>,
+  pub virtual_link: Option<String>,
+  pub description: Option<String>,
+  pub event_version: Option<EventVersion>,
+  pub authority_must_sign: Option<bool>,
+  pub refund_policy: Option<RefundPolicy>,
+  pub forfeit_policy: Option<ForfeitPolicy>,
+  pub check_in_mode: Option<CheckInMode>,
+  pub settlement_rule: Option<SettlementRule>,
+  pub check_out_policy: Option<CheckOutPolicy>,
+  pub amendment_window: Option<i64>,
+}
+
+impl UpdateEventArgs {
+  /// editors can only change what is displayed on the event collection
+  pub fn is_metadata_only(&self) -> bool {
+    self.commitment_fee.is_none()
+      && self.event_starts_at.is_none()
+      && self.event_ends_at.is_none()
+      && self.registration_starts_at.is_none()
+      && self.registration_ends_at.is_none()
+      && self.capacity.is_none()
+      && self.event_version.is_none()
+      && self.authority_must_sign.is_none()
+      && self.refund_policy.is_none()
+      && self.forfeit_policy.is_none()
+      && self.check_in_mode.is_none()
+      && self.settlement_rule.is_none()
+      && self.check_out_policy.is_none()
+      && self.amendment_window.is_none()
+  }
+}
+
+#[derive(Accounts)]
+pub struct UpdateEvent<'info> {
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED,
+        event.key().as_ref(),
+        EVENT_COLLECTION_SUFFIX_SEED,
+      ],
+      bump,
+      constraint = event_collection.update_authority == community.key(),
+  )]
+  pub event_collection: Box<Account<'info, BaseCollectionV1>>,
+  /// community authority or an editor of the event
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+  /// CHECK: This is checked by the address constraint
+  #[account(address = MPL_CORE_ID)]
+  pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+impl<'info> UpdateEvent<'info> {
+  pub fn update_event_collection(&self, args: UpdateEventArgs) -> Result<()> {
+    let (_, collection_attributes, _) = fetch_plugin::<BaseCollectionV1, Attributes>(
+      &self.event_collection.to_account_info(),
+      PluginType::Attributes,
+    )?;
+    let mut attribute_list = collection_attributes.attribute_list;
+
+    let schedule = &self.event.schedule;
+
+    macro_rules! update_optional_attribute {
+      ($key:expr, $value:expr) => {
+        if let Some(value) = $value {
+          upsert_attribute(&mut attribute_list, $key, value.to_string());
+        }
+      };
+    }
+
+    update_optional_attribute!("Event Type", args.event_type.map(|t| format!("{:?}", t)));
+    update_optional_attribute!("Organizer", args.organizer);
+    update_optional_attribute!("Fee", args.commitment_fee);
+    // display copy of the event schedule
+    update_optional_attribute!("Event Starts At", Some(schedule.starts_at.unwrap_or(0)));
+    update_optional_attribute!("Event Ends At", schedule.ends_at);
+    update_optional_attribute!("Registration Starts At", schedule.registration_starts_at);
+    update_optional_attribute!("Registration Ends At", schedule.registration_ends_at);
+    update_optional_attribute!("Capacity", args.capacity);
+    update_optional_attribute!("Location", args.location);
+    update_optional_attribute!("Virtual Link", args.virtual_link);
+    update_optional_attribute!("Description", args.description);
+
+    let signer_seeds = &[
+      COMMUNITY_PRE_SEED,
+      self.community.seed.as_ref(),
+      &[self.community.bump],
+    ];
+
+    UpdateCollectionPluginV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
+      .collection(&self.event_collection.to_account_info())
+      .payer(&self.authority.to_account_info())
+      .authority(Some(&self.community.to_account_info()))
+      .system_program(&self.system_program.to_account_info())
+      .plugin(Plugin::Attributes(Attributes { attribute_list }))
+      .invoke_signed(&[signer_seeds])?;
+
+    if args.name.is_some() || args.uri.is_some() {
+      let mpl_core_program = self.mpl_core_program.to_account_info();
+      let event_collection = self.event_collection.to_account_info();
+      let authority = self.authority.to_account_info();
+      let community = self.community.to_account_info();
+      let system_program = self.system_program.to_account_info();
+
+      let mut update_collection_cpi = UpdateCollectionV1CpiBuilder::new(&mpl_core_program);
+      update_collection_cpi
+        .collection(&event_collection)
+        .payer(&authority)
+        .authority(Some(&community))
+        .system_program(&system_program);
+      if let Some(name) = args.name {
+        update_collection_cpi.new_name(name);
+      }
+      if let Some(uri) = args.uri {
+        update_collection_cpi.new_uri(uri);
+      }
+      update_collection_cpi.invoke_signed(&[signer_seeds])?;
+    }
+
+    Ok(())
+  }
+}
+
+pub fn update_event_handler(ctx: Context<UpdateEvent>, args: UpdateEventArgs) -> Result<()> {
+  let event = &mut ctx.accounts.event;
+
+  event.assert_allows(EventAction::Update)?;
+
+  let community_authority = ctx.accounts.community.authority;
+  if ctx.accounts.authority.key() != community_authority {
+    event.assert_role(
+      &ctx.accounts.authority.key(),
+      &community_authority,
+      ROLE_EDITOR,
+    )?;
+    require!(args.is_metadata_only(), FoshoErrors::MissingEventRole);
+  }
+
+  // attendees already paid the current fee, it is refunded as is in claim_rewards
+  if let Some(commitment_fee) = args.commitment_fee {
+    require!(
+      event.attendees_count == 0 || commitment_fee == event.commitment_fee,
+      FoshoErrors::CommitmentFeeLocked
+    );
+    event.commitment_fee = commitment_fee;
+  }
+  if let Some(event_version) = args.event_version.clone() {
+    event_version.validate()?;
+    event.event_version = event_version;
+  }
+  if let Some(authority_must_sign) = args.authority_must_sign {
+    event.authority_must_sign = authority_must_sign;
+  }
+
+  // the new schedule has to pass the same checks as in create_event
+  if let Some(event_starts_at) = args.event_starts_at {
+    validate_event_start_time(event_starts_at)?;
+    event.schedule.starts_at = Some(event_starts_at);
+  }
+  if let Some(event_ends_at) = args.event_ends_at {
+    event.schedule.ends_at = Some(event_ends_at);
+  }
+  if let Some(registration_starts_at) = args.registration_starts_at {
+    event.schedule.registration_starts_at = Some(registration_starts_at);
+  }
+  if let Some(registration_ends_at) = args.registration_ends_at {
+    event.schedule.registration_ends_at = Some(registration_ends_at);
+  }
+  // 0 stands for an unlimited capacity
+  if let Some(capacity) = args.capacity {
+    require!(
+      capacity.eq(&0) || capacity >= u64::from(event.attendees_count),
+      FoshoErrors::InvalidCapacity
+    );
+    event.schedule.capacity = if capacity.eq(&0) {
+      None
+    } else {
+      Some(capacity)
+    };
+  }
+  event.schedule.validate()?;
+
+  // attendees joined under the current refund policy
+  if let Some(refund_policy) = args.refund_policy.clone() {
+    refund_policy.validate()?;
+    require!(
+      event.attendees_count == 0 || refund_policy == event.refund_policy,
+      FoshoErrors::RefundPolicyLocked
+    );
+    event.refund_policy = refund_policy;
+  }
+  // attendees joined under the current forfeit policy
+  if let Some(forfeit_policy) = args.forfeit_policy.clone() {
+    require!(
+      event.attendees_count == 0 || forfeit_policy == event.forfeit_policy,
+      FoshoErrors::ForfeitPolicyLocked
+    );
+    event.forfeit_policy = forfeit_policy;
+  }
+  if let Some(check_in_mode) = args.check_in_mode.clone() {
+    event.check_in_mode = check_in_mode;
+  }
+  // attendees joined under the current settlement rule
+  if let Some(settlement_rule) = args.settlement_rule.clone() {
+    settlement_rule.validate(event.sessions_count)?;
+    require!(
+      event.attendees_count == 0 || settlement_rule == event.settlement_rule,
+      FoshoErrors::SettlementRuleLocked
+    );
+    event.settlement_rule = settlement_rule;
+  }
+  // attendees joined under the current check-out policy
+  if let Some(check_out_policy) = args.check_out_policy.clone() {
+    check_out_policy.validate()?;
+    require!(
+      event.attendees_count == 0 || check_out_policy == event.check_out_policy,
+      FoshoErrors::CheckOutPolicyLocked
+    );
+    event.check_out_policy = check_out_policy;
+  }
+  if let Some(amendment_window) = args.amendment_window {
+    require_gte!(amendment_window, 0, FoshoErrors::InvalidAmendmentWindow);
+    event.amendment_window = amendment_window;
+  }
+  // session check-ins do not have a check-out
+  require!(
+    !event.check_out_policy.is_required() || event.settlement_rule == SettlementRule::SingleCheckIn,
+    FoshoErrors::InvalidCheckOutPolicy
+  );
+
+  ctx.accounts.update_event_collection(args)
+}
@@ -3,20 +3,14 @@ use crate::{
   error::FoshoErrors,
   state::*,
   utils::{
-    check_if_already_scanned, get_event_ends_at_from_attributes,
-    get_event_starts_at_from_attributes,
+    check_if_already_scanned, verify_ed25519_signature, write_ticket_scan, TicketCpiAccounts,
   },
 };
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::sysvar::instructions as sysvar_instructions};
 
 use mpl_core::{
   accounts::{BaseAssetV1, BaseCollectionV1},
-  fetch_plugin,
-  instructions::{UpdatePluginV1CpiBuilder, WriteExternalPluginAdapterDataV1CpiBuilder},
-  types::{
-    Attributes, ExternalPluginAdapterKey, PermanentFreezeDelegate, Plugin, PluginAuthority,
-    PluginType, UpdateAuthority,
-  },
+  types::UpdateAuthority,
   ID as MPL_CORE_ID,
 };
 
@@ -43,6 +37,30 @@ pub struct VerifyAttendee<'info> {
     has_one = community,
   )]
   pub event: Box<Account<'info, Event>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
+  /// created here for the attendees who joined before attendance histories existed
+  #[account(
+    init_if_needed,
+    payer = event_authority,
+    space = 8 + AttendanceHistory::INIT_SPACE,
+    seeds = [
+      ATTENDANCE_PRE_SEED,
+      community.key().as_ref(),
+      attendee_record.owner.as_ref()
+    ],
+    bump,
+  )]
+  pub attendance_history: Box<Account<'info, AttendanceHistory>>,
   #[account(
     seeds = [
       COMMUNITY_PRE_SEED.as_ref(),
@@ -83,80 +101,79 @@ pub struct VerifyAttendee<'info> {
   /// CHECK: This is checked by the address constraint
   #[account(address = MPL_CORE_ID)]
   pub mpl_core_program: UncheckedAccount<'info>,
+  /// required by the proof of presence check-in mode
+  /// CHECK: checked by the address constraint
+  #[account(address = sysvar_instructions::ID)]
+  pub instructions_sysvar: Option<UncheckedAccount<'info>>,
 }
 
 impl<'info> VerifyAttendee<'info> {
+  /// the Ed25519 instruction verifying the signature of the ticket owner
+  /// over the challenge has to precede this instruction.
+  pub fn assert_presence(&self, challenge: Option<CheckInChallenge>) -> Result<()> {
+    let max_age_slots = match self.event.check_in_mode {
+      CheckInMode::Staff => return Ok(()),
+      CheckInMode::ProofOfPresence { max_age_slots } => max_age_slots,
+    };
+    let challenge = challenge.ok_or(FoshoErrors::StaleCheckInChallenge)?;
+    let current_slot = Clock::get()?.slot;
+    require!(
+      challenge.slot <= current_slot && current_slot - challenge.slot <= max_age_slots,
+      FoshoErrors::StaleCheckInChallenge
+    );
+
+    let instructions_sysvar = self
+      .instructions_sysvar
+      .as_ref()
+      .ok_or(FoshoErrors::AccountNotProvided)?;
+    verify_ed25519_signature(
+      instructions_sysvar,
+      &self.owner.key(),
+      &challenge.message(&self.event.key(), &self.attendee_record.key()),
+    )
+  }
+
   pub fn scan_ticket(&self) -> Result<()> {
     // check if community authority scanned
     check_if_already_scanned(self.ticket.to_account_info(), &self.community.key())?;
 
     // If we get here, no authorities have scanned yet, so we can continue
-    let (_, collection_attribute_list, _) = fetch_plugin::<BaseCollectionV1, Attributes>(
-      &self.event_collection.to_account_info(),
-      PluginType::Attributes,
-    )?;
-
-    let event_starts_at =
-      get_event_starts_at_from_attributes(&collection_attribute_list.attribute_list)?;
-
-    let event_ends_at =
-      get_event_ends_at_from_attributes(&collection_attribute_list.attribute_list)?;
-
-    let current_unix_ts = Clock::get()?.unix_timestamp as u64;
-    if event_starts_at.ne(&0) {
-      require!(
-        current_unix_ts >= event_starts_at,
-        FoshoErrors::EventHasNotStarted
-      );
-    }
-
-    if event_ends_at.ne(&0) {
-      require!(current_unix_ts <= event_ends_at, FoshoErrors::EventEnded);
-    }
-
-    let data: Vec<u8> = "Verified".as_bytes().to_vec();
-
-    let signer_seeds = &[
-      COMMUNITY_PRE_SEED.as_ref(),
-      self.community.seed.as_ref(),
-      &[self.community.bump],
-    ];
-
-    // The event authority is the `signer` of this instruction.
-    WriteExternalPluginAdapterDataV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
-      .asset(&self.ticket.to_account_info())
-      .collection(Some(&self.event_collection.to_account_info()))
-      .payer(&self.event_authority.to_account_info())
-      .authority(Some(&self.community.to_account_info()))
-      .system_program(&self.system_program.to_account_info())
-      .key(ExternalPluginAdapterKey::AppData(
-        PluginAuthority::Address {
-          address: self.community.key(),
-        },
-      ))
-      .data(data)
-      .invoke_signed(&[signer_seeds])?;
-
-    UpdatePluginV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
-      .asset(&self.ticket.to_account_info())
-      .collection(Some(&self.event_collection.to_account_info()))
-      .payer(&self.event_authority.to_account_info())
-      .authority(Some(&self.community.to_account_info()))
-      .system_program(&self.system_program.to_account_info())
-      .plugin(Plugin::PermanentFreezeDelegate(PermanentFreezeDelegate {
-        frozen: true,
-      }))
-      .invoke_signed(&[signer_seeds])?;
-
-    Ok(())
+    self
+      .event
+      .schedule
+      .assert_in_progress(Clock::get()?.unix_timestamp)?;
+
+    // attendees are only verified on check-out if the event requires one
+    let result = if self.event.check_out_policy.is_required() {
+      "Checked In"
+    } else {
+      "Verified"
+    };
+    write_ticket_scan(
+      &TicketCpiAccounts {
+        mpl_core_program: &self.mpl_core_program.to_account_info(),
+        ticket: &self.ticket.to_account_info(),
+        event_collection: &self.event_collection.to_account_info(),
+        payer: &self.event_authority.to_account_info(),
+        community: &self.community,
+        system_program: &self.system_program.to_account_info(),
+      },
+      result,
+    )
   }
 }
 
-pub fn verify_attendee_handler(ctx: Context<VerifyAttendee>) -> Result<()> {
+pub fn verify_attendee_handler(
+  ctx: Context<VerifyAttendee>,
+  challenge: Option<CheckInChallenge>,
+) -> Result<()> {
+  ctx.accounts.assert_presence(challenge)?;
+
   let attendee_record = &mut ctx.accounts.attendee_record;
   let event = &ctx.accounts.event;
 
-  require!(!event.is_cancelled, FoshoErrors::EventCancelled);
+  event.assert_allows(EventAction::Verify)?;
+  event.settlement_rule.assert_single_check_in()?;
 
   match attendee_record.status {
     AttendeeStatus::Pending => {}
@@ -166,24 +183,28 @@ pub fn verify_attendee_handler(ctx: Context<VerifyAttendee>) -> Result<()> {
     AttendeeStatus::Rejected => {
       return Err(FoshoErrors::AlreadyScanned.into());
     }
-    AttendeeStatus::Verified => {
+    AttendeeStatus::Verified | AttendeeStatus::PartiallyAttended => {
       return Err(FoshoErrors::AlreadyScanned.into());
     }
   }
 
-  let is_community_authority =
-    ctx.accounts.event_authority.key() == ctx.accounts.community.authority;
-
-  if !is_community_authority {
-    require!(
-      event
-        .event_authorities
-        .contains(&ctx.accounts.event_authority.key()),
-      FoshoErrors::InvalidEventAuthority
-    );
-  }
-
-  attendee_record.status = AttendeeStatus::Verified;
+  event.assert_role(
+    &ctx.accounts.event_authority.key(),
+    &ctx.accounts.community.authority,
+    ROLE_SCANNER,
+  )?;
+
+  ctx.accounts.attendance_history.init_if_new(
+    ctx.accounts.community.key(),
+    attendee_record.owner,
+    ctx.bumps.attendance_history,
+  );
+  attendee_record.check_in(
+    event,
+    &mut ctx.accounts.vault,
+    &mut ctx.accounts.attendance_history,
+    Clock::get()?.unix_timestamp,
+  )?;
 
   ctx.accounts.scan_ticket()?;
   Ok(())
@@ -0,0 +1,210 @@
+use crate::{
+  constant::*,
+  error::FoshoErrors,
+  state::*,
+  utils::{freeze_ticket, write_ticket_app_data, TicketCpiAccounts},
+};
+use anchor_lang::prelude::*;
+
+use mpl_core::{
+  accounts::{BaseAssetV1, BaseCollectionV1},
+  types::UpdateAuthority,
+  ID as MPL_CORE_ID,
+};
+
+#[derive(Accounts)]
+pub struct VerifySession<'info> {
+  #[account(
+    mut,
+    seeds = [
+      ATTENDEE_PRE_SEED,
+      event.key().as_ref(),
+      attendee_record.owner.key().as_ref()
+    ],
+    bump = attendee_record.bump,
+    has_one = event,
+  )]
+  pub attendee_record: Box<Account<'info, Attendee>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      SESSION_SUFFIX_SEED,
+      &[session.index],
+    ],
+    bump = session.bump,
+    has_one = event,
+  )]
+  pub session: Box<Account<'info, Session>>,
+  #[account(
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    mut,
+    seeds = [
+      EVENT_PRE_SEED,
+      event.key().as_ref(),
+      VAULT_SUFFIX_SEED,
+    ],
+    bump = vault.bump,
+    has_one = event,
+  )]
+  pub vault: Box<Account<'info, EventVault>>,
+  /// created here for the attendees who joined before attendance histories existed
+  #[account(
+    init_if_needed,
+    payer = event_authority,
+    space = 8 + AttendanceHistory::INIT_SPACE,
+    seeds = [
+      ATTENDANCE_PRE_SEED,
+      community.key().as_ref(),
+      attendee_record.owner.as_ref()
+    ],
+    bump,
+  )]
+  pub attendance_history: Box<Account<'info, AttendanceHistory>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED,
+        event.key().as_ref(),
+        EVENT_COLLECTION_SUFFIX_SEED,
+      ],
+      bump,
+      constraint = event_collection.update_authority == community.key(),
+  )]
+  pub event_collection: Box<Account<'info, BaseCollectionV1>>,
+  #[account(
+      mut,
+      seeds = [
+        EVENT_PRE_SEED,
+        event.key().as_ref(),
+        attendee_record.owner.key().as_ref(),
+        TICKET_SUFFIX_SEED,
+      ],
+      bump,
+      constraint = ticket.owner == owner.key(),
+      constraint = ticket.update_authority == UpdateAuthority::Collection(event_collection.key()),
+  )]
+  pub ticket: Box<Account<'info, BaseAssetV1>>,
+  pub system_program: Program<'info, System>,
+  /// CHECK: This is checked by the ticket constraint
+  pub owner: AccountInfo<'info>,
+  #[account(mut)]
+  pub event_authority: Signer<'info>,
+  /// CHECK: This is checked by the address constraint
+  #[account(address = MPL_CORE_ID)]
+  pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+impl<'info> VerifySession<'info> {
+  /// the app data of the ticket holds the session bitmap as a little endian u64,
+  /// the ticket is frozen on the first check-in.
+  pub fn scan_ticket(&self, first_check_in: bool) -> Result<()> {
+    let accounts = TicketCpiAccounts {
+      mpl_core_program: &self.mpl_core_program.to_account_info(),
+      ticket: &self.ticket.to_account_info(),
+      event_collection: &self.event_collection.to_account_info(),
+      payer: &self.event_authority.to_account_info(),
+      community: &self.community,
+      system_program: &self.system_program.to_account_info(),
+    };
+    write_ticket_app_data(
+      &accounts,
+      self
+        .attendee_record
+        .sessions_attended
+        .to_le_bytes()
+        .to_vec(),
+    )?;
+    if first_check_in {
+      freeze_ticket(&accounts)?;
+    }
+    Ok(())
+  }
+}
+
+pub fn verify_session_handler(ctx: Context<VerifySession>) -> Result<()> {
+  let event = &ctx.accounts.event;
+
+  event.assert_allows(EventAction::Verify)?;
+  require!(
+    event.settlement_rule != SettlementRule::SingleCheckIn,
+    FoshoErrors::InvalidSettlementRule
+  );
+  // the owners cannot sign a check-in challenge per session
+  require!(
+    event.check_in_mode == CheckInMode::Staff,
+    FoshoErrors::ProofOfPresenceRequired
+  );
+  event.assert_role(
+    &ctx.accounts.event_authority.key(),
+    &ctx.accounts.community.authority,
+    ROLE_SCANNER,
+  )?;
+
+  let session = &mut ctx.accounts.session;
+  let current_time = Clock::get()?.unix_timestamp;
+  require!(
+    current_time >= session.starts_at && current_time <= session.ends_at,
+    FoshoErrors::SessionNotInProgress
+  );
+
+  let attendee_record = &mut ctx.accounts.attendee_record;
+  match attendee_record.status {
+    // late sessions are still recorded once the attendee is verified
+    AttendeeStatus::Pending | AttendeeStatus::Verified => {}
+    AttendeeStatus::Claimed => {
+      return Err(FoshoErrors::AlreadyClaimed.into());
+    }
+    AttendeeStatus::Rejected | AttendeeStatus::PartiallyAttended => {
+      return Err(FoshoErrors::AlreadyScanned.into());
+    }
+  }
+
+  let session_bit = 1u64 << session.index;
+  require!(
+    attendee_record.sessions_attended & session_bit == 0,
+    FoshoErrors::AlreadyScanned
+  );
+  let first_check_in = attendee_record.sessions_attended == 0;
+  attendee_record.sessions_attended |= session_bit;
+  session.attendees_count = session.attendees_count.checked_add(1).unwrap();
+
+  ctx.accounts.attendance_history.init_if_new(
+    ctx.accounts.community.key(),
+    attendee_record.owner,
+    ctx.bumps.attendance_history,
+  );
+
+  if attendee_record.status == AttendeeStatus::Pending
+    && event
+      .settlement_rule
+      .is_met(attendee_record.sessions_attended)
+      == Some(true)
+  {
+    attendee_record.verify(
+      &mut ctx.accounts.vault,
+      &mut ctx.accounts.attendance_history,
+      event.nonce,
+    )?;
+  }
+
+  ctx.accounts.scan_ticket(first_check_in)
+}
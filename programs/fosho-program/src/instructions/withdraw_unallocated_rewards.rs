@@ -0,0 +1,91 @@
+use crate::{constant::*, error::FoshoErrors, state::*, utils::transfer_from_event};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct WithdrawUnallocatedRewards<'info> {
+  #[account(
+    seeds = [
+      EVENT_PRE_SEED,
+      community.key().as_ref(),
+      &event.nonce.to_le_bytes()
+    ],
+    bump = event.bump,
+    has_one = community,
+    constraint = event.reward_mint == Some(reward_mint.key()) @ FoshoErrors::PublicKeyMismatch,
+  )]
+  pub event: Box<Account<'info, Event>>,
+  #[account(
+    seeds = [
+      COMMUNITY_PRE_SEED,
+      community.seed.as_ref(),
+    ],
+    bump = community.bump,
+  )]
+  pub community: Box<Account<'info, Community>>,
+  #[account(
+    mint::token_program = token_program,
+  )]
+  pub reward_mint: InterfaceAccount<'info, Mint>,
+  #[account(
+    mut,
+    associated_token::mint = reward_mint,
+    associated_token::authority = event,
+    associated_token::token_program = token_program
+  )]
+  pub reward_account: InterfaceAccount<'info, TokenAccount>,
+  #[account(
+    mut,
+    associated_token::mint = reward_mint,
+    associated_token::authority = community.authority,
+    associated_token::token_program = token_program
+  )]
+  pub receiver_account: InterfaceAccount<'info, TokenAccount>,
+  /// required once the governance of the community is enabled
+  #[account(mut, has_one = community)]
+  pub proposal: Option<Box<Account<'info, Proposal>>>,
+  /// community authority or a treasurer of the event,
+  /// anyone executing an approved proposal once the governance is enabled
+  pub authority: Signer<'info>,
+  pub token_program: Interface<'info, TokenInterface>,
+}
+
+// rewards owed to registered attendees stay in the reward account,
+// withdrawn rewards always go to the community authority.
+pub fn withdraw_unallocated_rewards_handler(
+  ctx: Context<WithdrawUnallocatedRewards>,
+  amount: u64,
+) -> Result<()> {
+  if ctx.accounts.community.is_governed() {
+    ctx.accounts.community.authorize(
+      &ctx.accounts.authority.key(),
+      ctx.accounts.proposal.as_deref_mut(),
+      ProposalAction::WithdrawRewards {
+        event: ctx.accounts.event.key(),
+        amount,
+      },
+    )?;
+  } else {
+    ctx.accounts.event.assert_role(
+      &ctx.accounts.authority.key(),
+      &ctx.accounts.community.authority,
+      ROLE_TREASURER,
+    )?;
+  }
+
+  let unallocated = ctx
+    .accounts
+    .reward_account
+    .amount
+    .saturating_sub(ctx.accounts.event.reward_liability);
+  require_gte!(unallocated, amount, FoshoErrors::RewardsAllocated);
+
+  transfer_from_event(
+    &ctx.accounts.event,
+    ctx.accounts.reward_account.to_account_info(),
+    ctx.accounts.receiver_account.to_account_info(),
+    &ctx.accounts.reward_mint,
+    ctx.accounts.token_program.to_account_info(),
+    amount,
+  )
+}
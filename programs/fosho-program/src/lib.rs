@@ -5,8 +5,14 @@ declare_id!("GZNvuxENwSG5jAsCJVLvVrhXyoF1ong3Gx98YAwKfhZe");
 
 mod instructions;
 use instructions::*;
+use state::AttendeeStatus;
+use state::CheckInChallenge;
+use state::EventAuthority;
 use state::EventType;
 use state::EventVersion;
+use state::Invitation;
+use state::MemberRole;
+use state::ProposalAction;
 pub mod constant;
 pub mod error;
 pub mod state;
@@ -25,6 +31,73 @@ pub mod fosho_program {
     create_community_handler(ctx, seed, community_name)
   }
 
+  pub fn update_community(ctx: Context<UpdateCommunity>, args: UpdateCommunityArgs) -> Result<()> {
+    log_version();
+    update_community_handler(ctx, args)
+  }
+
+  pub fn propose_community_authority(
+    ctx: Context<ProposeCommunityAuthority>,
+    new_authority: Option<Pubkey>,
+  ) -> Result<()> {
+    log_version();
+    propose_community_authority_handler(ctx, new_authority)
+  }
+
+  pub fn accept_community_authority(ctx: Context<AcceptCommunityAuthority>) -> Result<()> {
+    log_version();
+    accept_community_authority_handler(ctx)
+  }
+
+  pub fn set_approvers(
+    ctx: Context<SetApprovers>,
+    approvers: Vec<Pubkey>,
+    threshold: u8,
+  ) -> Result<()> {
+    log_version();
+    set_approvers_handler(ctx, approvers, threshold)
+  }
+
+  pub fn create_proposal(ctx: Context<CreateProposal>, action: ProposalAction) -> Result<()> {
+    log_version();
+    create_proposal_handler(ctx, action)
+  }
+
+  pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+    log_version();
+    approve_proposal_handler(ctx)
+  }
+
+  pub fn close_proposal(ctx: Context<CloseProposal>) -> Result<()> {
+    log_version();
+    close_proposal_handler(ctx)
+  }
+
+  pub fn invite_member(ctx: Context<InviteMember>, role: MemberRole) -> Result<()> {
+    log_version();
+    invite_member_handler(ctx, role)
+  }
+
+  pub fn accept_invitation(ctx: Context<AcceptInvitation>) -> Result<()> {
+    log_version();
+    accept_invitation_handler(ctx)
+  }
+
+  pub fn join_community(ctx: Context<JoinCommunity>) -> Result<()> {
+    log_version();
+    join_community_handler(ctx)
+  }
+
+  pub fn approve_member(ctx: Context<ManageMember>) -> Result<()> {
+    log_version();
+    approve_member_handler(ctx)
+  }
+
+  pub fn remove_member(ctx: Context<ManageMember>) -> Result<()> {
+    log_version();
+    remove_member_handler(ctx)
+  }
+
   #[inline(never)]
   pub fn create_event(
     ctx: Context<CreateEvent>,
@@ -45,7 +118,7 @@ pub mod fosho_program {
     reward_per_user: u64,
     // event_authorities can sign join_event ixn
     // and the verify_attendance ixn
-    event_authorities: Vec<Pubkey>,
+    event_authorities: Vec<EventAuthority>,
     // authorities must sign join_event ixn
     authority_must_sign: bool,
   ) -> Result<()> {
@@ -72,14 +145,87 @@ pub mod fosho_program {
     )
   }
 
-  pub fn join_event(ctx: Context<JoinEvent>) -> Result<()> {
+  pub fn update_event(ctx: Context<UpdateEvent>, args: UpdateEventArgs) -> Result<()> {
+    log_version();
+    update_event_handler(ctx, args)
+  }
+
+  pub fn publish_event(ctx: Context<UpdateEventStatus>) -> Result<()> {
+    log_version();
+    publish_event_handler(ctx)
+  }
+
+  pub fn start_check_in(ctx: Context<UpdateEventStatus>) -> Result<()> {
+    log_version();
+    start_check_in_handler(ctx)
+  }
+
+  pub fn end_event(ctx: Context<UpdateEventStatus>) -> Result<()> {
+    log_version();
+    end_event_handler(ctx)
+  }
+
+  pub fn settle_event(ctx: Context<SettleEvent>) -> Result<()> {
+    log_version();
+    settle_event_handler(ctx)
+  }
+
+  pub fn create_ticket_tier(
+    ctx: Context<CreateTicketTier>,
+    name: String,
+    uri: String,
+    commitment_fee: u64,
+    reward_per_user: u64,
+    capacity: Option<u64>,
+    event_version: EventVersion,
+  ) -> Result<()> {
+    log_version();
+    create_ticket_tier_handler(
+      ctx,
+      name,
+      uri,
+      commitment_fee,
+      reward_per_user,
+      capacity,
+      event_version,
+    )
+  }
+
+  pub fn create_session(
+    ctx: Context<CreateSession>,
+    starts_at: i64,
+    ends_at: i64,
+    location: Option<String>,
+  ) -> Result<()> {
     log_version();
-    join_event_handler(ctx)
+    create_session_handler(ctx, starts_at, ends_at, location)
   }
 
-  pub fn verify_attendee(ctx: Context<VerifyAttendee>) -> Result<()> {
+  pub fn join_event(
+    ctx: Context<JoinEvent>,
+    allowlist_proof: Option<AllowlistProof>,
+    invitation: Option<Invitation>,
+  ) -> Result<()> {
+    log_version();
+    join_event_handler(ctx, allowlist_proof, invitation)
+  }
+
+  pub fn set_allowlist_root(ctx: Context<SetAllowlistRoot>, merkle_root: [u8; 32]) -> Result<()> {
+    log_version();
+    set_allowlist_root_handler(ctx, merkle_root)
+  }
+
+  pub fn unjoin_event(ctx: Context<UnjoinEvent>) -> Result<()> {
+    log_version();
+    unjoin_event_handler(ctx)
+  }
+
+  pub fn verify_attendee(
+    ctx: Context<VerifyAttendee>,
+    challenge: Option<CheckInChallenge>,
+  ) -> Result<()> {
     log_version();
-    verify_attendee_handler(ctx)
+    verify_attendee_handler(ctx, challenge)
   }
 
   pub fn reject_attendee(ctx: Context<RejectAttendee>) -> Result<()> {
@@ -87,6 +233,45 @@ pub mod fosho_program {
     reject_attendee_handler(ctx)
   }
 
+  pub fn check_out_attendee(ctx: Context<CheckOutAttendee>) -> Result<()> {
+    log_version();
+    check_out_attendee_handler(ctx)
+  }
+
+  pub fn verify_attendees<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchScanAttendees<'info>>,
+  ) -> Result<Vec<SkippedScan>> {
+    log_version();
+    verify_attendees_handler(ctx)
+  }
+
+  pub fn reject_attendees<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchScanAttendees<'info>>,
+  ) -> Result<Vec<SkippedScan>> {
+    log_version();
+    reject_attendees_handler(ctx)
+  }
+
+  pub fn verify_session(ctx: Context<VerifySession>) -> Result<()> {
+    log_version();
+    verify_session_handler(ctx)
+  }
+
+  pub fn amend_attendance(ctx: Context<AmendAttendance>, status: AttendeeStatus) -> Result<()> {
+    log_version();
+    amend_attendance_handler(ctx, status)
+  }
+
+  pub fn appeal_rejection(ctx: Context<AppealRejection>, reason: String) -> Result<()> {
+    log_version();
+    appeal_rejection_handler(ctx, reason)
+  }
+
+  pub fn resolve_appeal(ctx: Context<ResolveAppeal>, approve: bool) -> Result<()> {
+    log_version();
+    resolve_appeal_handler(ctx, approve)
+  }
+
   pub fn cancel_event(ctx: Context<CancelEvent>) -> Result<()> {
     log_version();
     cancel_event_handler(ctx)
@@ -96,6 +281,64 @@ pub mod fosho_program {
     log_version();
     claim_rewards_handler(ctx)
   }
+
+  pub fn close_event<'info>(ctx: Context<'_, '_, 'info, 'info, CloseEvent<'info>>) -> Result<()> {
+    log_version();
+    close_event_handler(ctx)
+  }
+
+  pub fn reconcile_vault(ctx: Context<ReconcileVault>) -> Result<()> {
+    log_version();
+    reconcile_vault_handler(ctx)
+  }
+
+  pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+    log_version();
+    fund_rewards_handler(ctx, amount)
+  }
+
+  pub fn withdraw_unallocated_rewards(
+    ctx: Context<WithdrawUnallocatedRewards>,
+    amount: u64,
+  ) -> Result<()> {
+    log_version();
+    withdraw_unallocated_rewards_handler(ctx, amount)
+  }
+
+  pub fn add_event_authority(
+    ctx: Context<ManageEventAuthority>,
+    event_authority: Pubkey,
+    roles: u8,
+  ) -> Result<()> {
+    log_version();
+    add_event_authority_handler(ctx, event_authority, roles)
+  }
+
+  pub fn remove_event_authority(
+    ctx: Context<ManageEventAuthority>,
+    event_authority: Pubkey,
+  ) -> Result<()> {
+    log_version();
+    remove_event_authority_handler(ctx, event_authority)
+  }
+
+  pub fn replace_event_authority(
+    ctx: Context<ManageEventAuthority>,
+    old_event_authority: Pubkey,
+    new_event_authority: Pubkey,
+  ) -> Result<()> {
+    log_version();
+    replace_event_authority_handler(ctx, old_event_authority, new_event_authority)
+  }
+
+  pub fn set_event_authority_roles(
+    ctx: Context<ManageEventAuthority>,
+    event_authority: Pubkey,
+    roles: u8,
+  ) -> Result<()> {
+    log_version();
+    set_event_authority_roles_handler(ctx, event_authority, roles)
+  }
 }
 
 fn log_version() {
@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_APPEAL_REASON_LEN: usize = 200;
+
+/// Appeal of a rejected attendee, resolved by the organizer with resolve_appeal.
+#[account]
+#[derive(InitSpace)]
+pub struct Appeal {
+  pub attendee_record: Pubkey,
+  pub event: Pubkey,
+  pub owner: Pubkey,
+  pub bump: u8,
+  pub status: AppealStatus,
+  pub created_at: i64,
+  #[max_len(MAX_APPEAL_REASON_LEN)]
+  pub reason: String,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum AppealStatus {
+  Pending,
+  Approved,
+  Denied,
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+/// number of past events covered by `recent_events`
+pub const ATTENDANCE_WINDOW: u32 = 64;
+
+/// Verified attendances of a wallet across the events of a community
+#[account]
+#[derive(InitSpace)]
+pub struct AttendanceHistory {
+  pub community: Pubkey,
+  pub owner: Pubkey,
+  pub bump: u8,
+  pub verified_count: u32,
+  /// highest event nonce the owner was verified at
+  pub last_verified_nonce: u32,
+  /// bit `n` is set if the owner was verified at the event `last_verified_nonce - n`
+  pub recent_events: u64,
+}
+
+impl AttendanceHistory {
+  /// sets up an account created by init_if_needed, existing histories are left untouched
+  pub fn init_if_new(&mut self, community: Pubkey, owner: Pubkey, bump: u8) {
+    if self.owner == Pubkey::default() {
+      self.community = community;
+      self.owner = owner;
+      self.bump = bump;
+    }
+  }
+
+  pub fn record_verified(&mut self, nonce: u32) {
+    self.verified_count = self.verified_count.saturating_add(1);
+    if self.verified_count == 1 || nonce > self.last_verified_nonce {
+      let shift = nonce.saturating_sub(self.last_verified_nonce);
+      self.recent_events = if self.verified_count == 1 || shift >= ATTENDANCE_WINDOW {
+        0
+      } else {
+        self.recent_events << shift
+      };
+      self.recent_events |= 1;
+      self.last_verified_nonce = nonce;
+    } else if self.last_verified_nonce - nonce < ATTENDANCE_WINDOW {
+      self.recent_events |= 1 << (self.last_verified_nonce - nonce);
+    }
+  }
+
+  /// undoes record_verified when an attendance is amended
+  pub fn remove_verified(&mut self, nonce: u32) {
+    self.verified_count = self.verified_count.saturating_sub(1);
+    if self.attended(nonce) {
+      self.recent_events &= !(1 << (self.last_verified_nonce - nonce));
+    }
+  }
+
+  /// verified attendances among the `last_events` events created before `nonce`,
+  /// older events than the window are not counted
+  pub fn count_before(&self, nonce: u32, last_events: u32) -> u32 {
+    (nonce.saturating_sub(last_events)..nonce)
+      .filter(|past_nonce| self.attended(*past_nonce))
+      .count() as u32
+  }
+
+  fn attended(&self, nonce: u32) -> bool {
+    if self.verified_count == 0 || nonce > self.last_verified_nonce {
+      return false;
+    }
+    let offset = self.last_verified_nonce - nonce;
+    offset < ATTENDANCE_WINDOW && self.recent_events & (1 << offset) != 0
+  }
+}
@@ -1,5 +1,9 @@
 use anchor_lang::prelude::*;
 
+use crate::error::FoshoErrors;
+
+use super::{AttendanceHistory, Event, EventVault};
+
 // not used anymore
 #[account]
 pub struct Attendee {
@@ -7,10 +11,115 @@ pub struct Attendee {
   pub owner: Pubkey,
   pub bump: u8,
   pub status: AttendeeStatus,
+  /// index of the ticket tier, None for events without tiers
+  pub tier: Option<u8>,
+  /// commitment fee paid on join, refunded on claim
+  pub commitment_fee: u64,
+  /// reward paid out on claim
+  pub reward_amount: u64,
+  /// bit `i` is set once the attendee checked in to session `i`
+  pub sessions_attended: u64,
+  pub checked_in_at: Option<i64>,
+  /// set by check_out_attendee if the event requires a check-out
+  pub checked_out_at: Option<i64>,
 }
 
 impl Attendee {
-  pub const ATTENDEE_SIZE: usize = 32 + 32 + 1 + 1;
+  pub const ATTENDEE_SIZE: usize = 32 + 32 + 1 + 1 + 2 + 8 + 8 + 8 + 9 + 9;
+
+  /// the attendee is verified right away unless the event requires a check-out,
+  /// in which case the partial refund is owed from now on.
+  pub fn check_in(
+    &mut self,
+    event: &Event,
+    vault: &mut EventVault,
+    attendance_history: &mut AttendanceHistory,
+    current_time: i64,
+  ) -> Result<()> {
+    self.checked_in_at = Some(current_time);
+    if event.check_out_policy.is_required() {
+      let partial_refund = event.check_out_policy.partial_refund(self.commitment_fee)?;
+      vault.partial_refunds = vault
+        .partial_refunds
+        .checked_add(partial_refund)
+        .ok_or(FoshoErrors::NumericalOverflow)?;
+      return Ok(());
+    }
+    self.verify(vault, attendance_history, event.nonce)
+  }
+
+  pub fn verify(
+    &mut self,
+    vault: &mut EventVault,
+    attendance_history: &mut AttendanceHistory,
+    nonce: u32,
+  ) -> Result<()> {
+    self.status = AttendeeStatus::Verified;
+    vault.verified_fees = vault
+      .verified_fees
+      .checked_add(self.commitment_fee)
+      .ok_or(FoshoErrors::NumericalOverflow)?;
+    attendance_history.record_verified(nonce);
+    Ok(())
+  }
+
+  /// corrects the scan result of the attendee to Verified or Rejected
+  pub fn amend(
+    &mut self,
+    status: AttendeeStatus,
+    event: &Event,
+    vault: &mut EventVault,
+    attendance_history: &mut AttendanceHistory,
+  ) -> Result<()> {
+    require!(
+      matches!(status, AttendeeStatus::Verified | AttendeeStatus::Rejected)
+        && status != self.status,
+      FoshoErrors::InvalidAmendment
+    );
+
+    match self.status {
+      AttendeeStatus::Verified => {
+        vault.verified_fees = vault
+          .verified_fees
+          .checked_sub(self.commitment_fee)
+          .ok_or(FoshoErrors::NumericalOverflow)?;
+        attendance_history.remove_verified(event.nonce);
+      }
+      AttendeeStatus::PartiallyAttended => self.release_partial_refund(event, vault)?,
+      // checked in but never checked out, e.g. a missed check-out scan
+      AttendeeStatus::Pending if self.checked_in_at.is_some() => {
+        self.release_partial_refund(event, vault)?
+      }
+      // checked in to fewer sessions than the settlement rule requires
+      AttendeeStatus::Pending if self.sessions_attended != 0 => {}
+      AttendeeStatus::Rejected => {}
+      // not scanned yet, verify_attendee and reject_attendee apply
+      AttendeeStatus::Pending => return err!(FoshoErrors::InvalidAmendment),
+      AttendeeStatus::Claimed => return err!(FoshoErrors::AlreadyClaimed),
+    }
+
+    if status == AttendeeStatus::Verified {
+      self.verify(vault, attendance_history, event.nonce)
+    } else {
+      self.status = AttendeeStatus::Rejected;
+      Ok(())
+    }
+  }
+
+  /// the partial refund set aside on check-in is not owed anymore
+  fn release_partial_refund(&self, event: &Event, vault: &mut EventVault) -> Result<()> {
+    let partial_refund = event.check_out_policy.partial_refund(self.commitment_fee)?;
+    vault.partial_refunds = vault
+      .partial_refunds
+      .checked_sub(partial_refund)
+      .ok_or(FoshoErrors::NumericalOverflow)?;
+    Ok(())
+  }
+
+  /// checked in but never checked out, settles as partially attended
+  pub fn left_without_check_out(&self) -> bool {
+    self.status == AttendeeStatus::Pending && self.checked_in_at.is_some()
+  }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
@@ -19,4 +128,6 @@ pub enum AttendeeStatus {
   Verified,
   Rejected,
   Claimed,
+  /// checked in but left before the check-out requirement of the event was met
+  PartiallyAttended,
 }
@@ -1,5 +1,15 @@
 use anchor_lang::prelude::*;
 
+use crate::error::FoshoErrors;
+
+use super::{Proposal, ProposalAction};
+
+pub const MAX_COMMUNITY_NAME_LEN: usize = 50;
+pub const MAX_COMMUNITY_URI_LEN: usize = 200;
+pub const MAX_SOCIAL_LINKS: usize = 4;
+pub const MAX_SOCIAL_LINK_LEN: usize = 100;
+pub const MAX_APPROVERS: usize = 10;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Community {
@@ -7,6 +17,113 @@ pub struct Community {
   pub authority: Pubkey,
   pub events_count: u32,
   pub bump: u8,
-  #[max_len(50)]
+  #[max_len(MAX_COMMUNITY_NAME_LEN)]
   pub name: String,
+  /// proposed by the current authority, becomes the authority once accepted
+  pub pending_authority: Option<Pubkey>,
+  /// off-chain metadata of the community
+  #[max_len(MAX_COMMUNITY_URI_LEN)]
+  pub uri: String,
+  #[max_len(MAX_COMMUNITY_URI_LEN)]
+  pub logo: String,
+  #[max_len(MAX_SOCIAL_LINKS, MAX_SOCIAL_LINK_LEN)]
+  pub social_links: Vec<String>,
+  /// M-of-N governance, sensitive actions require a proposal
+  /// approved by `threshold` approvers. Disabled while threshold is 0.
+  #[max_len(MAX_APPROVERS)]
+  pub approvers: Vec<Pubkey>,
+  pub threshold: u8,
+  pub proposals_count: u32,
+  /// lamports paid to the community authority on join_community
+  pub membership_fee: u64,
+  /// members joining by themselves stay pending until approved
+  pub membership_requires_approval: bool,
+  /// number of active members
+  pub members_count: u32,
+}
+
+impl Community {
+  pub fn validate(&self) -> Result<()> {
+    require!(
+      self.name.len() <= MAX_COMMUNITY_NAME_LEN,
+      FoshoErrors::CommunityNameTooLong
+    );
+    require!(
+      self.uri.len() <= MAX_COMMUNITY_URI_LEN
+        && self.logo.len() <= MAX_COMMUNITY_URI_LEN
+        && self.social_links.len() <= MAX_SOCIAL_LINKS
+        && self
+          .social_links
+          .iter()
+          .all(|link| link.len() <= MAX_SOCIAL_LINK_LEN),
+      FoshoErrors::InvalidCommunityMetadata
+    );
+    Ok(())
+  }
+
+  pub fn validate_approvers(approvers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+      approvers.len() <= MAX_APPROVERS && usize::from(threshold) <= approvers.len(),
+      FoshoErrors::InvalidApprovers
+    );
+    for (index, approver) in approvers.iter().enumerate() {
+      require!(
+        !approvers[..index].contains(approver),
+        FoshoErrors::InvalidApprovers
+      );
+    }
+    Ok(())
+  }
+
+  pub fn is_governed(&self) -> bool {
+    self.threshold > 0
+  }
+
+  /// without governance only the community authority can run the action,
+  /// otherwise the approved proposal of the action is executed.
+  pub fn authorize(
+    &self,
+    signer: &Pubkey,
+    proposal: Option<&mut Account<Proposal>>,
+    action: ProposalAction,
+  ) -> Result<()> {
+    if !self.is_governed() {
+      require_keys_eq!(*signer, self.authority, FoshoErrors::InvalidAuthority);
+      return Ok(());
+    }
+
+    // the proposal accounts are constrained with has_one = community
+    let proposal = proposal.ok_or(FoshoErrors::ProposalRequired)?;
+    self.assert_approved(proposal, &action)?;
+
+    // proposals of repeated actions are reused until they expire
+    if !matches!(action, ProposalAction::ClaimForfeits { .. }) {
+      proposal.executed = true;
+    }
+    Ok(())
+  }
+
+  fn assert_approved(&self, proposal: &Proposal, action: &ProposalAction) -> Result<()> {
+    require!(
+      proposal.action == *action,
+      FoshoErrors::ProposalActionMismatch
+    );
+    require!(!proposal.executed, FoshoErrors::ProposalAlreadyExecuted);
+    require!(
+      !proposal.is_expired(Clock::get()?.unix_timestamp),
+      FoshoErrors::ProposalExpired
+    );
+
+    // approvers removed since their approval do not count anymore
+    let approvals = proposal
+      .approvals
+      .iter()
+      .filter(|approver| self.approvers.contains(approver))
+      .count();
+    require!(
+      approvals >= usize::from(self.threshold),
+      FoshoErrors::ProposalNotApproved
+    );
+    Ok(())
+  }
 }
@@ -1,4 +1,13 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::hash::hashv};
+
+use crate::{
+  constant::{
+    ALL_ROLES, BPS_DENOMINATOR, CHECK_IN_CHALLENGE_PREFIX, CLAIM_PERIOD, MAX_EVENT_AUTHORITIES,
+  },
+  error::FoshoErrors,
+};
+
+use super::{GateNode, ATTENDANCE_WINDOW, MAX_GATE_NODES};
 
 #[account]
 #[derive(InitSpace)]
@@ -7,16 +16,497 @@ pub struct Event {
   pub reward_mint: Option<Pubkey>,
   pub event_version: EventVersion,
   /// 4 event authorities are allowed.
-  #[max_len(4)]
-  pub event_authorities: Vec<Pubkey>,
+  #[max_len(MAX_EVENT_AUTHORITIES)]
+  pub event_authorities: Vec<EventAuthority>,
   pub commitment_fee: u64,
+  /// token of the commitment fee, None for SOL
+  pub fee_mint: Option<Pubkey>,
   pub bump: u8,
   pub nonce: u32,
   pub reward_per_user: u64,
-  pub is_cancelled: bool,
+  pub status: EventStatus,
   /// in all cases, event authority must sign the attendance.
   /// if this is true. event authority must sign the join event instruction.
   pub authority_must_sign: bool,
+  /// source of truth for times and capacity,
+  /// the event collection attributes are only a display copy.
+  pub schedule: EventSchedule,
+  /// number of attendee records created for this event
+  pub attendees_count: u32,
+  /// number of attendee records that have been claimed or forfeited
+  pub claimed_count: u32,
+  /// set once the event is settled or cancelled, starts the claim period
+  pub settled_at: Option<i64>,
+  /// applied when an attendee leaves the event with unjoin_event
+  pub refund_policy: RefundPolicy,
+  /// number of ticket tiers, join_event requires a tier if there is any
+  pub tiers_count: u8,
+  /// where the commitment fees of no-show and rejected attendees go
+  pub forfeit_policy: ForfeitPolicy,
+  /// reward tokens owed to registered attendees that have not claimed yet
+  pub reward_liability: u64,
+  pub check_in_mode: CheckInMode,
+  /// number of sessions, verify_session checks attendees in per session
+  pub sessions_count: u8,
+  /// decides which attendees are verified and earn their refund and reward
+  pub settlement_rule: SettlementRule,
+  /// attendees that check in but leave early only get a partial refund
+  pub check_out_policy: CheckOutPolicy,
+  /// seconds after the event ends during which scan results can be amended
+  pub amendment_window: i64,
+}
+
+impl Event {
+  pub fn transition_to(&mut self, next_status: EventStatus) -> Result<()> {
+    require!(
+      self.status.can_transition_to(next_status),
+      FoshoErrors::InvalidStatusTransition
+    );
+    self.status = next_status;
+    Ok(())
+  }
+
+  /// every attendee record has been claimed or the claim period is over
+  pub fn is_closable(&self, current_time: i64) -> bool {
+    if self.claimed_count >= self.attendees_count {
+      return true;
+    }
+    match self.settled_at {
+      Some(settled_at) => current_time >= settled_at.saturating_add(CLAIM_PERIOD),
+      None => false,
+    }
+  }
+
+  /// at most MAX_EVENT_AUTHORITIES distinct keys with valid roles
+  pub fn validate_event_authorities(event_authorities: &[EventAuthority]) -> Result<()> {
+    require!(
+      event_authorities.len() <= MAX_EVENT_AUTHORITIES,
+      FoshoErrors::TooManyEventAuthorities
+    );
+    for (index, event_authority) in event_authorities.iter().enumerate() {
+      require!(
+        event_authority.roles != 0 && event_authority.roles & !ALL_ROLES == 0,
+        FoshoErrors::InvalidRoles
+      );
+      require!(
+        !event_authorities[..index]
+          .iter()
+          .any(|other| other.key == event_authority.key),
+        FoshoErrors::DuplicateEventAuthority
+      );
+    }
+    Ok(())
+  }
+
+  pub fn position_of_event_authority(&self, key: &Pubkey) -> Result<usize> {
+    self
+      .event_authorities
+      .iter()
+      .position(|event_authority| event_authority.key == *key)
+      .ok_or(FoshoErrors::EventAuthorityNotFound.into())
+  }
+
+  /// the community authority holds every role
+  pub fn assert_role(&self, key: &Pubkey, community_authority: &Pubkey, role: u8) -> Result<()> {
+    if key == community_authority {
+      return Ok(());
+    }
+    let event_authority = self
+      .event_authorities
+      .iter()
+      .find(|event_authority| event_authority.key == *key)
+      .ok_or(FoshoErrors::InvalidEventAuthority)?;
+    require!(
+      event_authority.roles & role == role,
+      FoshoErrors::MissingEventRole
+    );
+    Ok(())
+  }
+
+  /// scan results can be corrected until the amendment window closes, before settlement
+  pub fn assert_amendable(&self, current_time: i64) -> Result<()> {
+    self.assert_allows(EventAction::Amend)?;
+    if let Some(ends_at) = self.schedule.ends_at {
+      require!(
+        current_time <= ends_at.saturating_add(self.amendment_window),
+        FoshoErrors::AmendmentWindowClosed
+      );
+    }
+    Ok(())
+  }
+
+  /// scan results are final once the event ended and its amendment window closed
+  pub fn assert_settleable(&self, current_time: i64) -> Result<()> {
+    if let Some(ends_at) = self.schedule.ends_at {
+      require!(
+        current_time > ends_at.saturating_add(self.amendment_window),
+        FoshoErrors::AmendmentWindowOpen
+      );
+    }
+    Ok(())
+  }
+
+  pub fn assert_allows(&self, action: EventAction) -> Result<()> {
+    require!(
+      self.status != EventStatus::Cancelled || action == EventAction::Claim,
+      FoshoErrors::EventCancelled
+    );
+    require!(self.status.allows(action), FoshoErrors::InvalidEventStatus);
+    Ok(())
+  }
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace, Default)]
+pub struct EventSchedule {
+  pub starts_at: Option<i64>,
+  pub ends_at: Option<i64>,
+  pub registration_starts_at: Option<i64>,
+  pub registration_ends_at: Option<i64>,
+  /// None means an unlimited capacity
+  pub capacity: Option<u64>,
+}
+
+impl EventSchedule {
+  /// registration has to close before the event starts, and both periods cannot be empty
+  pub fn validate(&self) -> Result<()> {
+    if let (Some(starts_at), Some(registration_ends_at)) =
+      (self.starts_at, self.registration_ends_at)
+    {
+      require_gte!(
+        starts_at,
+        registration_ends_at,
+        FoshoErrors::InvalidRegistrationEndTime
+      );
+    }
+    if let (Some(starts_at), Some(ends_at)) = (self.starts_at, self.ends_at) {
+      require_gt!(ends_at, starts_at, FoshoErrors::InvalidEventEndTime);
+    }
+    if let (Some(registration_starts_at), Some(registration_ends_at)) =
+      (self.registration_starts_at, self.registration_ends_at)
+    {
+      require_gte!(
+        registration_ends_at,
+        registration_starts_at,
+        FoshoErrors::InvalidRegistrationStartTime
+      );
+    }
+    Ok(())
+  }
+
+  pub fn assert_registration_open(&self, current_time: i64) -> Result<()> {
+    if let Some(registration_starts_at) = self.registration_starts_at {
+      require!(
+        current_time >= registration_starts_at,
+        FoshoErrors::RegistrationNotStarted
+      );
+    }
+    if let Some(registration_ends_at) = self.registration_ends_at {
+      require!(
+        current_time <= registration_ends_at,
+        FoshoErrors::RegistrationEnded
+      );
+    }
+    Ok(())
+  }
+
+  pub fn assert_started(&self, current_time: i64) -> Result<()> {
+    if let Some(starts_at) = self.starts_at {
+      require!(current_time >= starts_at, FoshoErrors::EventHasNotStarted);
+    }
+    Ok(())
+  }
+
+  pub fn assert_over(&self, current_time: i64) -> Result<()> {
+    if let Some(ends_at) = self.ends_at {
+      require!(current_time > ends_at, FoshoErrors::EventHasNotEnded);
+    }
+    Ok(())
+  }
+
+  pub fn assert_in_progress(&self, current_time: i64) -> Result<()> {
+    self.assert_started(current_time)?;
+    if let Some(ends_at) = self.ends_at {
+      require!(current_time <= ends_at, FoshoErrors::EventEnded);
+    }
+    Ok(())
+  }
+}
+
+/// Draft -> Published -> Live -> Ended -> Settled -> Closed
+/// an event can be cancelled until it ends.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum EventStatus {
+  /// prepared by the organizer, not open for registration
+  Draft,
+  /// registration is open
+  Published,
+  /// check-in is open, late registrations are still possible
+  Live,
+  /// check-in is closed, no-shows can still be rejected
+  Ended,
+  /// attendance is final, rewards and commitment fees can be claimed
+  Settled,
+  Closed,
+  Cancelled,
+}
+
+/// Refunds are relative to the event start, the default policy
+/// refunds the full commitment fee until the event starts.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace, Default)]
+pub struct RefundPolicy {
+  /// full refund while the event starts in more than this many seconds
+  pub full_refund_cutoff: i64,
+  /// share of the commitment fee refunded after the cutoff, in basis points
+  pub partial_refund_bps: u16,
+  /// no refund once the event starts in less than this many seconds
+  pub no_refund_window: i64,
+}
+
+impl RefundPolicy {
+  pub fn validate(&self) -> Result<()> {
+    require!(
+      self.partial_refund_bps <= BPS_DENOMINATOR
+        && self.no_refund_window <= self.full_refund_cutoff,
+      FoshoErrors::InvalidRefundPolicy
+    );
+    Ok(())
+  }
+
+  pub fn refund_amount(
+    &self,
+    commitment_fee: u64,
+    starts_at: Option<i64>,
+    current_time: i64,
+  ) -> Result<u64> {
+    let starts_at = match starts_at {
+      Some(starts_at) => starts_at,
+      None => return Ok(commitment_fee),
+    };
+    let time_left = starts_at.saturating_sub(current_time);
+
+    if time_left >= self.full_refund_cutoff {
+      Ok(commitment_fee)
+    } else if time_left >= self.no_refund_window {
+      let refund = (commitment_fee as u128)
+        .checked_mul(self.partial_refund_bps as u128)
+        .ok_or(FoshoErrors::NumericalOverflow)?
+        / BPS_DENOMINATOR as u128;
+      Ok(refund as u64)
+    } else {
+      Ok(0)
+    }
+  }
+}
+
+/// roles is a bitmask of the ROLE_* constants
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct EventAuthority {
+  pub key: Pubkey,
+  pub roles: u8,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace, Default)]
+pub enum ForfeitPolicy {
+  /// claimed by the community authority
+  #[default]
+  ToOrganizer,
+  /// shared among verified attendees in proportion to their commitment fee
+  ProRata,
+  /// sent to a charity or treasury address
+  Treasury { address: Pubkey },
+  /// burned for token fees, sent to the incinerator for SOL fees
+  Burn,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace, Default)]
+pub enum CheckInMode {
+  /// the signature of a scanner is enough
+  #[default]
+  Staff,
+  /// the ticket owner also signs the challenge displayed at the venue,
+  /// challenges older than `max_age_slots` are rejected
+  ProofOfPresence { max_age_slots: u64 },
+}
+
+/// Displayed at the venue by the scanner, which rotates `venue_nonce` and only
+/// submits the nonces it displayed.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug)]
+pub struct CheckInChallenge {
+  pub slot: u64,
+  pub venue_nonce: [u8; 32],
+}
+
+impl CheckInChallenge {
+  /// message signed by the ticket owner, bound to its attendee record so it
+  /// cannot check in another ticket
+  pub fn message(&self, event: &Pubkey, attendee_record: &Pubkey) -> [u8; 32] {
+    hashv(&[
+      CHECK_IN_CHALLENGE_PREFIX,
+      event.as_ref(),
+      attendee_record.as_ref(),
+      &self.slot.to_le_bytes(),
+      &self.venue_nonce,
+    ])
+    .to_bytes()
+  }
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace, Default)]
+pub enum SettlementRule {
+  /// verified by a single verify_attendee scan
+  #[default]
+  SingleCheckIn,
+  /// verified once checked in to `min_attended` sessions with verify_session
+  MinSessions { min_attended: u8 },
+}
+
+impl SettlementRule {
+  /// the sessions of the event have to be enough to meet the rule
+  pub fn validate(&self, sessions_count: u8) -> Result<()> {
+    if let SettlementRule::MinSessions { min_attended } = self {
+      require!(
+        *min_attended > 0 && *min_attended <= sessions_count,
+        FoshoErrors::InvalidSettlementRule
+      );
+    }
+    Ok(())
+  }
+
+  /// None for the single check-in rule, which sessions do not settle
+  pub fn is_met(&self, sessions_attended: u64) -> Option<bool> {
+    match self {
+      SettlementRule::SingleCheckIn => None,
+      SettlementRule::MinSessions { min_attended } => {
+        Some(sessions_attended.count_ones() >= u32::from(*min_attended))
+      }
+    }
+  }
+
+  pub fn assert_single_check_in(&self) -> Result<()> {
+    require!(
+      *self == SettlementRule::SingleCheckIn,
+      FoshoErrors::SessionCheckInRequired
+    );
+    Ok(())
+  }
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace, Default)]
+pub enum CheckOutPolicy {
+  /// attendees are verified on check-in
+  #[default]
+  NotRequired,
+  /// attendees have to stay at least `min_presence` seconds after their check-in
+  MinPresence {
+    min_presence: i64,
+    partial_refund_bps: u16,
+  },
+  /// attendees have to check out between `opens_at` and `closes_at`,
+  /// check-outs before the window settle as partially attended
+  Window {
+    opens_at: i64,
+    closes_at: i64,
+    partial_refund_bps: u16,
+  },
+}
+
+impl CheckOutPolicy {
+  pub fn validate(&self) -> Result<()> {
+    let is_valid = match self {
+      CheckOutPolicy::NotRequired => true,
+      CheckOutPolicy::MinPresence {
+        min_presence,
+        partial_refund_bps,
+      } => *min_presence > 0 && *partial_refund_bps <= BPS_DENOMINATOR,
+      CheckOutPolicy::Window {
+        opens_at,
+        closes_at,
+        partial_refund_bps,
+      } => opens_at < closes_at && *partial_refund_bps <= BPS_DENOMINATOR,
+    };
+    require!(is_valid, FoshoErrors::InvalidCheckOutPolicy);
+    Ok(())
+  }
+
+  pub fn is_required(&self) -> bool {
+    *self != CheckOutPolicy::NotRequired
+  }
+
+  /// refund of an attendee who left early
+  pub fn partial_refund(&self, commitment_fee: u64) -> Result<u64> {
+    let partial_refund_bps = match self {
+      CheckOutPolicy::NotRequired => return Ok(commitment_fee),
+      CheckOutPolicy::MinPresence {
+        partial_refund_bps, ..
+      }
+      | CheckOutPolicy::Window {
+        partial_refund_bps, ..
+      } => *partial_refund_bps,
+    };
+    let refund = (commitment_fee as u128)
+      .checked_mul(partial_refund_bps as u128)
+      .ok_or(FoshoErrors::NumericalOverflow)?
+      / BPS_DENOMINATOR as u128;
+    Ok(refund as u64)
+  }
+
+  /// whether an attendee checking out now stayed long enough
+  pub fn is_met(&self, checked_in_at: i64, current_time: i64) -> Result<bool> {
+    match self {
+      CheckOutPolicy::NotRequired => err!(FoshoErrors::CheckOutNotRequired),
+      CheckOutPolicy::MinPresence { min_presence, .. } => {
+        Ok(current_time.saturating_sub(checked_in_at) >= *min_presence)
+      }
+      CheckOutPolicy::Window {
+        opens_at,
+        closes_at,
+        ..
+      } => {
+        require!(current_time <= *closes_at, FoshoErrors::CheckOutClosed);
+        Ok(current_time >= *opens_at)
+      }
+    }
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EventAction {
+  Update,
+  Join,
+  Unjoin,
+  Verify,
+  Reject,
+  Amend,
+  Claim,
+}
+
+impl EventStatus {
+  pub fn can_transition_to(&self, next_status: EventStatus) -> bool {
+    use EventStatus::*;
+    matches!(
+      (self, next_status),
+      (Draft, Published)
+        | (Published, Live)
+        | (Live, Ended)
+        | (Ended, Settled)
+        | (Settled, Closed)
+        | (Draft | Published | Live, Cancelled)
+        | (Cancelled, Closed)
+    )
+  }
+
+  pub fn allows(&self, action: EventAction) -> bool {
+    use EventStatus::*;
+    match action {
+      EventAction::Update => matches!(self, Draft | Published | Live),
+      EventAction::Join => matches!(self, Published | Live),
+      EventAction::Unjoin => matches!(self, Published | Live),
+      EventAction::Verify => matches!(self, Live),
+      EventAction::Reject => matches!(self, Live | Ended),
+      EventAction::Amend => matches!(self, Live | Ended),
+      EventAction::Claim => matches!(self, Settled | Cancelled),
+    }
+  }
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug)]
@@ -49,9 +539,153 @@ pub struct TokenData {
   pub minimum_amount: Option<u64>,
 }
 
+pub const MAX_ATTRIBUTE_KEY_LEN: usize = 32;
+pub const MAX_ATTRIBUTE_VALUE_LEN: usize = 64;
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct CoreCollectionData {
+  /// mpl-core collection set as the update authority of the asset
+  pub collection: Pubkey,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct CoreCreatorData {
+  /// verified creator of the VerifiedCreators plugin of the asset
+  pub creator: Pubkey,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct CoreAttributeData {
+  /// mpl-core collection set as the update authority of the asset
+  pub collection: Pubkey,
+  /// attribute of the Attributes plugin of the asset
+  #[max_len(MAX_ATTRIBUTE_KEY_LEN)]
+  pub key: String,
+  #[max_len(MAX_ATTRIBUTE_VALUE_LEN)]
+  pub value: String,
+}
+
+impl AttendanceData {
+  // Option::is_none_or is newer than the rustc of the SBF toolchain
+  #[allow(clippy::unnecessary_map_or)]
+  pub fn validate(&self) -> Result<()> {
+    require!(
+      self.min_count > 0
+        && self
+          .last_events
+          .map_or(true, |last_events| last_events <= ATTENDANCE_WINDOW),
+      FoshoErrors::InvalidAttendanceGate
+    );
+    Ok(())
+  }
+}
+
+impl CoreAttributeData {
+  pub fn validate(&self) -> Result<()> {
+    require!(
+      self.key.len() <= MAX_ATTRIBUTE_KEY_LEN && self.value.len() <= MAX_ATTRIBUTE_VALUE_LEN,
+      FoshoErrors::InvalidAssetAttribute
+    );
+    Ok(())
+  }
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct AttendanceData {
+  /// minimum number of verified attendances in the community
+  pub min_count: u32,
+  /// only counts the last events of the community if provided, at most ATTENDANCE_WINDOW
+  pub last_events: Option<u32>,
+}
+
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace)]
 pub enum EventVersion {
   Regular,
   NftGated(NftData),
   TokenGated(TokenData),
+  /// the attendee needs an active Member account in the community
+  MembersOnly,
+  /// the attendee needs an mpl-core asset of the collection,
+  /// e.g. a membership pass or a ticket of a past event
+  CoreCollectionGated(CoreCollectionData),
+  CoreCreatorGated(CoreCreatorData),
+  CoreAttributeGated(CoreAttributeData),
+  /// the attendee needs to have attended past events of the community
+  AttendanceGated(AttendanceData),
+  /// the attendee needs a merkle proof of their entry, see the fosho-allowlist crate
+  Allowlist {
+    merkle_root: [u8; 32],
+  },
+  /// composable rule expression, see GateNode
+  Rules(#[max_len(MAX_GATE_NODES)] Vec<GateNode>),
+}
+
+impl EventVersion {
+  pub fn validate(&self) -> Result<()> {
+    match self {
+      EventVersion::CoreAttributeGated(attribute_data) => attribute_data.validate(),
+      EventVersion::AttendanceGated(attendance_data) => attendance_data.validate(),
+      EventVersion::Rules(nodes) => GateNode::validate_rules(nodes),
+      _ => Ok(()),
+    }
+  }
+
+  /// single condition versions are rule trees of one node
+  pub fn gate_nodes(&self) -> Vec<GateNode> {
+    match self {
+      EventVersion::Regular => vec![],
+      EventVersion::NftGated(nft_data) => vec![GateNode::Nft(nft_data.clone())],
+      EventVersion::TokenGated(token_data) => vec![GateNode::Token(token_data.clone())],
+      EventVersion::MembersOnly => vec![GateNode::Member],
+      EventVersion::CoreCollectionGated(collection_data) => {
+        vec![GateNode::CoreCollection(collection_data.clone())]
+      }
+      EventVersion::CoreCreatorGated(creator_data) => {
+        vec![GateNode::CoreCreator(creator_data.clone())]
+      }
+      EventVersion::CoreAttributeGated(attribute_data) => {
+        vec![GateNode::CoreAttribute(attribute_data.clone())]
+      }
+      EventVersion::AttendanceGated(attendance_data) => {
+        vec![GateNode::Attendance(attendance_data.clone())]
+      }
+      EventVersion::Allowlist { merkle_root } => vec![GateNode::Allowlist {
+        merkle_root: *merkle_root,
+      }],
+      EventVersion::Rules(nodes) => nodes.clone(),
+    }
+  }
+
+  pub fn allowlist_roots(&self) -> Vec<[u8; 32]> {
+    self
+      .gate_nodes()
+      .into_iter()
+      .filter_map(|node| match node {
+        GateNode::Allowlist { merkle_root } => Some(merkle_root),
+        _ => None,
+      })
+      .collect()
+  }
+
+  /// replaces the root of every allowlist of the gate
+  pub fn set_allowlist_root(&mut self, root: [u8; 32]) -> Result<()> {
+    let mut replaced = false;
+    match self {
+      EventVersion::Allowlist { merkle_root } => {
+        *merkle_root = root;
+        replaced = true;
+      }
+      EventVersion::Rules(nodes) => {
+        for node in nodes.iter_mut() {
+          if let GateNode::Allowlist { merkle_root } = node {
+            *merkle_root = root;
+            replaced = true;
+          }
+        }
+      }
+      _ => {}
+    }
+    require!(replaced, FoshoErrors::NotAnAllowlistGate);
+    Ok(())
+  }
 }
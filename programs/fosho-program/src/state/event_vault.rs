@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+
+use crate::error::FoshoErrors;
+
+/// Escrow of the commitment fees of an event.
+/// SOL fees are held as lamports on top of the rent of this account,
+/// token fees are held by the associated token account of the vault.
+#[account]
+#[derive(InitSpace)]
+pub struct EventVault {
+  pub event: Pubkey,
+  pub bump: u8,
+  /// fees received from attendees
+  pub total_deposited: u64,
+  /// fees returned to the attendees who paid them
+  pub total_refunded: u64,
+  /// fees paid out to someone else than the attendee who paid them
+  pub total_forfeited: u64,
+  /// fees of the verified attendees, the base of the pro-rata shares
+  pub verified_fees: u64,
+  /// forfeited fees shared among verified attendees, set on settlement
+  pub forfeit_pool: u64,
+  /// refunds owed to checked in attendees that have not met the check-out requirement
+  pub partial_refunds: u64,
+}
+
+impl EventVault {
+  pub fn record_deposit(&mut self, amount: u64) -> Result<()> {
+    self.total_deposited = self
+      .total_deposited
+      .checked_add(amount)
+      .ok_or(FoshoErrors::NumericalOverflow)?;
+    Ok(())
+  }
+
+  pub fn record_refund(&mut self, amount: u64) -> Result<()> {
+    self.total_refunded = self
+      .total_refunded
+      .checked_add(amount)
+      .ok_or(FoshoErrors::NumericalOverflow)?;
+    Ok(())
+  }
+
+  pub fn record_forfeit(&mut self, amount: u64) -> Result<()> {
+    self.total_forfeited = self
+      .total_forfeited
+      .checked_add(amount)
+      .ok_or(FoshoErrors::NumericalOverflow)?;
+    Ok(())
+  }
+
+  /// share of the forfeit pool of a verified attendee
+  pub fn forfeit_share(&self, commitment_fee: u64) -> Result<u64> {
+    if self.verified_fees == 0 {
+      return Ok(0);
+    }
+    let share = (self.forfeit_pool as u128)
+      .checked_mul(commitment_fee as u128)
+      .ok_or(FoshoErrors::NumericalOverflow)?
+      / self.verified_fees as u128;
+    Ok(share as u64)
+  }
+
+  /// fees still owed to attendees
+  pub fn outstanding(&self) -> Result<u64> {
+    self
+      .total_deposited
+      .checked_sub(self.total_refunded)
+      .and_then(|amount| amount.checked_sub(self.total_forfeited))
+      .ok_or(FoshoErrors::NumericalOverflow.into())
+  }
+
+  /// the escrowed balance has to cover the fees still owed,
+  /// anyone can send more to the vault so a surplus is only logged
+  pub fn assert_reconciled(&self, balance: u64) -> Result<()> {
+    let outstanding = self.outstanding()?;
+    require_gte!(balance, outstanding, FoshoErrors::VaultOutOfBalance);
+    if balance > outstanding {
+      msg!("Vault surplus: {}", balance - outstanding);
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use crate::error::FoshoErrors;
+
+use super::{
+  AttendanceData, CoreAttributeData, CoreCollectionData, CoreCreatorData, NftData, TokenData,
+};
+
+pub const MAX_GATE_NODES: usize = 8;
+
+/// A gate rule expression flattened in prefix order, every operator is
+/// followed by its operands: `All([Token, Any([Nft, Member])])` is stored as
+/// `[All(2), Token, Any(2), Nft, Member]`.
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub enum GateNode {
+  /// passes if the next `n` rules pass
+  All(u8),
+  /// passes if one of the next `n` rules passes
+  Any(u8),
+  /// passes if the next rule fails, only Member, Attendance and Token with a mint
+  /// can be negated since the attendee cannot pick their accounts
+  Not,
+  /// remaining accounts: mint, token account of the attendee
+  Token(TokenData),
+  /// remaining accounts: mint, token account of the attendee, metadata
+  Nft(NftData),
+  /// remaining accounts: member account of the attendee
+  Member,
+  /// remaining accounts: asset of the attendee
+  CoreCollection(CoreCollectionData),
+  /// remaining accounts: asset of the attendee
+  CoreCreator(CoreCreatorData),
+  /// remaining accounts: asset of the attendee
+  CoreAttribute(CoreAttributeData),
+  /// no remaining accounts, reads the attendance history of the attendee
+  Attendance(AttendanceData),
+  /// no remaining accounts, checks the allowlist proof of the join_event arguments
+  Allowlist { merkle_root: [u8; 32] },
+}
+
+impl GateNode {
+  /// nodes have to form exactly one rule tree
+  pub fn validate_rules(nodes: &[GateNode]) -> Result<()> {
+    require!(
+      !nodes.is_empty() && nodes.len() <= MAX_GATE_NODES,
+      FoshoErrors::InvalidGateRules
+    );
+    let end = Self::validate_rule(nodes, 0)?;
+    require!(end == nodes.len(), FoshoErrors::InvalidGateRules);
+    Ok(())
+  }
+
+  /// returns the position following the rule starting at `position`
+  fn validate_rule(nodes: &[GateNode], position: usize) -> Result<usize> {
+    let node = nodes.get(position).ok_or(FoshoErrors::InvalidGateRules)?;
+    let mut next = position + 1;
+    match node {
+      GateNode::All(count) | GateNode::Any(count) => {
+        require!(*count > 0, FoshoErrors::InvalidGateRules);
+        for _ in 0..*count {
+          next = Self::validate_rule(nodes, next)?;
+        }
+      }
+      GateNode::Not => {
+        require!(
+          matches!(
+            nodes.get(next),
+            Some(GateNode::Member)
+              | Some(GateNode::Attendance(_))
+              | Some(GateNode::Token(TokenData { mint: Some(_), .. }))
+          ),
+          FoshoErrors::InvalidGateRules
+        );
+        next += 1;
+      }
+      GateNode::Token(token_data) => require!(
+        token_data.mint.is_some() || token_data.minimum_amount.is_some(),
+        FoshoErrors::InvalidGateRules
+      ),
+      GateNode::Nft(nft_data) => require!(
+        nft_data.collection_mint.is_some() || nft_data.verified_creator.is_some(),
+        FoshoErrors::InvalidGateRules
+      ),
+      GateNode::CoreAttribute(attribute_data) => attribute_data.validate()?,
+      GateNode::Attendance(attendance_data) => attendance_data.validate()?,
+      GateNode::Member
+      | GateNode::CoreCollection(_)
+      | GateNode::CoreCreator(_)
+      | GateNode::Allowlist { .. } => {}
+    }
+    Ok(next)
+  }
+}
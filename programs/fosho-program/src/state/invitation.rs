@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::constant::INVITATION_PREFIX;
+
+/// Signed off-chain by an event authority with the approver role, see `message`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Invitation {
+  pub event: Pubkey,
+  /// None for an invitation link that any wallet can use
+  pub invitee: Option<Pubkey>,
+  pub expires_at: i64,
+  /// the invitee can only join with this ticket tier if provided
+  pub tier: Option<u8>,
+  pub max_uses: u16,
+  /// chosen by the event authority, unique per invitation
+  pub nonce: u64,
+}
+
+impl Invitation {
+  /// the prefix and the program id keep the signature from being valid for
+  /// any other message or program
+  pub fn message(&self) -> Result<Vec<u8>> {
+    let mut message = [INVITATION_PREFIX, crate::ID.as_ref()].concat();
+    message.extend(self.try_to_vec()?);
+    Ok(message)
+  }
+}
+
+/// Counts the uses of an invitation, created on its first use
+#[account]
+#[derive(InitSpace)]
+pub struct InvitationRecord {
+  pub event: Pubkey,
+  pub authority: Pubkey,
+  pub nonce: u64,
+  pub bump: u8,
+  pub uses: u16,
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use super::Community;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Member {
+  pub community: Pubkey,
+  pub wallet: Pubkey,
+  pub bump: u8,
+  /// set once the membership becomes active
+  pub joined_at: i64,
+  pub role: MemberRole,
+  pub status: MemberStatus,
+  /// membership fee held by the account until the application is approved or rejected
+  pub escrowed_fee: u64,
+}
+
+impl Member {
+  pub fn activate(&mut self, community: &mut Community) -> Result<()> {
+    self.status = MemberStatus::Active;
+    self.joined_at = Clock::get()?.unix_timestamp;
+    community.members_count = community.members_count.checked_add(1).unwrap();
+    Ok(())
+  }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum MemberRole {
+  Member,
+  /// can approve and reject pending applications
+  Moderator,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum MemberStatus {
+  /// invited by the community authority, accepted by the wallet
+  Invited,
+  /// joined by the wallet, approved by the community authority
+  Pending,
+  Active,
+  Removed,
+}
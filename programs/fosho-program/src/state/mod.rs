@@ -6,3 +6,30 @@ mod community;
 
 pub use attendee::*;
 mod attendee;
+
+pub use ticket_tier::*;
+mod ticket_tier;
+
+pub use event_vault::*;
+mod event_vault;
+
+pub use proposal::*;
+mod proposal;
+
+pub use member::*;
+mod member;
+
+pub use gate_rule::*;
+mod gate_rule;
+
+pub use attendance_history::*;
+mod attendance_history;
+
+pub use invitation::*;
+mod invitation;
+
+pub use session::*;
+mod session;
+
+pub use appeal::*;
+mod appeal;
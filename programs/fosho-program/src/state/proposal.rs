@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use super::MAX_APPROVERS;
+use crate::constant::PROPOSAL_LIFETIME;
+
+/// Sensitive community action, executed by the matching instruction
+/// once enough approvers of the community approved it.
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+  pub community: Pubkey,
+  pub index: u32,
+  pub bump: u8,
+  pub proposer: Pubkey,
+  pub action: ProposalAction,
+  #[max_len(MAX_APPROVERS)]
+  pub approvals: Vec<Pubkey>,
+  pub executed: bool,
+  pub created_at: i64,
+}
+
+impl Proposal {
+  pub fn is_expired(&self, current_time: i64) -> bool {
+    current_time > self.created_at.saturating_add(PROPOSAL_LIFETIME)
+  }
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub enum ProposalAction {
+  CancelEvent {
+    event: Pubkey,
+  },
+  CloseEvent {
+    event: Pubkey,
+  },
+  WithdrawRewards {
+    event: Pubkey,
+    amount: u64,
+  },
+  ChangeAuthority {
+    new_authority: Option<Pubkey>,
+  },
+  SetApprovers {
+    #[max_len(MAX_APPROVERS)]
+    approvers: Vec<Pubkey>,
+    threshold: u8,
+  },
+  /// stays open until it expires, every forfeited fee of the event is claimed with it
+  ClaimForfeits {
+    event: Pubkey,
+  },
+}
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+/// sessions are tracked in the u64 bitmap of the attendee record
+pub const MAX_SESSIONS: u8 = 64;
+pub const MAX_SESSION_LOCATION_LEN: usize = 64;
+
+/// A check-in window of a multi-session event, e.g. a day of a conference.
+#[account]
+#[derive(InitSpace)]
+pub struct Session {
+  pub event: Pubkey,
+  /// bit of the session in the attendance bitmap of the attendee records
+  pub index: u8,
+  pub bump: u8,
+  pub starts_at: i64,
+  pub ends_at: i64,
+  #[max_len(MAX_SESSION_LOCATION_LEN)]
+  pub location: Option<String>,
+  /// number of attendees checked in to this session
+  pub attendees_count: u32,
+}
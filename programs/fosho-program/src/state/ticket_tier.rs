@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use super::EventVersion;
+
+pub const MAX_TICKET_TIER_NAME_LEN: usize = 32;
+pub const MAX_TICKET_TIER_URI_LEN: usize = 200;
+
+#[account]
+#[derive(InitSpace)]
+pub struct TicketTier {
+  pub event: Pubkey,
+  pub index: u8,
+  pub bump: u8,
+  #[max_len(MAX_TICKET_TIER_NAME_LEN)]
+  pub name: String,
+  /// artwork of the tickets minted for this tier
+  #[max_len(MAX_TICKET_TIER_URI_LEN)]
+  pub uri: String,
+  pub commitment_fee: u64,
+  pub reward_per_user: u64,
+  /// None means an unlimited capacity, the event capacity still applies
+  pub capacity: Option<u64>,
+  pub attendees_count: u32,
+  /// checked on top of the gating rule of the event
+  pub event_version: EventVersion,
+}
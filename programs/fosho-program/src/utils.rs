@@ -1,26 +1,39 @@
 use anchor_lang::{
   prelude::*,
-  solana_program::{program_memory::sol_memcmp, pubkey::PUBKEY_BYTES},
+  solana_program::{
+    ed25519_program,
+    program_memory::sol_memcmp,
+    pubkey::PUBKEY_BYTES,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+  },
+  system_program,
 };
 
 use anchor_spl::{
   associated_token::get_associated_token_address_with_program_id,
   metadata::MetadataAccount,
-  token_2022::spl_token_2022::{extension::StateWithExtensions, state::Account},
+  token_2022::spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount},
+  token_interface::{burn, transfer_checked, Burn, Mint, TransferChecked},
 };
 use arrayref::array_ref;
 use mpl_core::{
   accounts::BaseAssetV1,
-  fetch_external_plugin_adapter_data_info,
+  fetch_asset_plugin, fetch_external_plugin_adapter_data_info,
+  instructions::{UpdatePluginV1CpiBuilder, WriteExternalPluginAdapterDataV1CpiBuilder},
   types::{
     AppDataInitInfo, Attribute, Attributes, ExternalPluginAdapterInitInfo,
-    ExternalPluginAdapterKey, ExternalPluginAdapterSchema, PermanentBurnDelegate,
+    ExternalPluginAdapterKey, ExternalPluginAdapterSchema, Key as CoreKey, PermanentBurnDelegate,
     PermanentFreezeDelegate, PermanentTransferDelegate, Plugin, PluginAuthority,
-    PluginAuthorityPair,
+    PluginAuthorityPair, PluginType, UpdateAuthority, VerifiedCreators,
   },
+  ID as MPL_CORE_ID,
 };
 
-use crate::error::FoshoErrors;
+use crate::{
+  constant::{COMMUNITY_PRE_SEED, EVENT_PRE_SEED, VAULT_SUFFIX_SEED},
+  error::FoshoErrors,
+  state::{Community, Event, EventVault},
+};
 
 pub fn create_attribute<K: Into<String>, V: Into<String>>(key: K, value: V) -> Attribute {
   Attribute {
@@ -29,67 +42,28 @@ pub fn create_attribute<K: Into<String>, V: Into<String>>(key: K, value: V) -> A
   }
 }
 
-pub fn get_capacity_from_attributes(attribute_list: &[Attribute]) -> Result<u32> {
-  let capacity_attribute = attribute_list.iter().find(|attr| attr.key == "Capacity");
-  match capacity_attribute {
-    Some(capacity_attribute) => capacity_attribute
-      .value
-      .parse::<u32>()
-      .map_err(|_| FoshoErrors::NumericalOverflow.into()),
-    None => return Ok(0),
-  }
-}
-
-pub fn get_reg_starts_at_from_attributes(attribute_list: &[Attribute]) -> Result<u64> {
-  let reg_starts_at = attribute_list
-    .iter()
-    .find(|attr| attr.key == "Registration Starts At");
-  match reg_starts_at {
-    Some(reg_starts_at) => reg_starts_at
-      .value
-      .parse::<u64>()
-      .map_err(|_| FoshoErrors::NumericalOverflow.into()),
-    None => return Ok(0),
-  }
-}
-
-pub fn get_reg_ends_at_from_attributes(attribute_list: &[Attribute]) -> Result<u64> {
-  let reg_ends_at = attribute_list
-    .iter()
-    .find(|attr| attr.key == "Registration Ends At");
-  match reg_ends_at {
-    Some(reg_ends_at) => reg_ends_at
-      .value
-      .parse::<u64>()
-      .map_err(|_| FoshoErrors::NumericalOverflow.into()),
-    None => return Ok(0),
-  }
-}
-
-pub fn get_event_ends_at_from_attributes(attribute_list: &[Attribute]) -> Result<u64> {
-  let event_ends_at = attribute_list
-    .iter()
-    .find(|attr| attr.key == "Event Ends At");
-  match event_ends_at {
-    Some(event_ends_at) => event_ends_at
-      .value
-      .parse::<u64>()
-      .map_err(|_| FoshoErrors::NumericalOverflow.into()),
-    None => return Ok(0),
+/// Replaces the value of an existing attribute or appends it if the key is missing.
+pub fn upsert_attribute<K: Into<String>, V: Into<String>>(
+  attribute_list: &mut Vec<Attribute>,
+  key: K,
+  value: V,
+) {
+  let key = key.into();
+  let value = value.into();
+  match attribute_list.iter_mut().find(|attr| attr.key == key) {
+    Some(attribute) => attribute.value = value,
+    None => attribute_list.push(Attribute { key, value }),
   }
 }
 
-pub fn get_event_starts_at_from_attributes(attribute_list: &[Attribute]) -> Result<u64> {
-  let event_starts_at = attribute_list
-    .iter()
-    .find(|attr| attr.key == "Event Starts At");
-  match event_starts_at {
-    Some(event_starts_at) => event_starts_at
-      .value
-      .parse::<u64>()
-      .map_err(|_| FoshoErrors::NumericalOverflow.into()),
-    None => return Ok(0),
-  }
+pub fn validate_event_start_time(event_starts_at: i64) -> Result<()> {
+  let current_time = Clock::get()?.unix_timestamp;
+  require_gt!(
+    event_starts_at,
+    current_time,
+    FoshoErrors::InvalidEventStartTime
+  );
+  Ok(())
 }
 
 pub fn create_ticket_plugins(
@@ -143,6 +117,72 @@ pub fn check_if_already_scanned<'a>(ticket: AccountInfo<'a>, authority: &Pubkey)
   Ok(require!(app_data_length == 0, FoshoErrors::AlreadyScanned))
 }
 
+/// Accounts of the mpl-core instructions updating a ticket, the community signs
+/// as the update authority of the event collection.
+pub struct TicketCpiAccounts<'a, 'info> {
+  pub mpl_core_program: &'a AccountInfo<'info>,
+  pub ticket: &'a AccountInfo<'info>,
+  pub event_collection: &'a AccountInfo<'info>,
+  pub payer: &'a AccountInfo<'info>,
+  pub community: &'a Account<'info, Community>,
+  pub system_program: &'a AccountInfo<'info>,
+}
+
+/// Writes the scan result in the app data of the ticket and freezes it.
+pub fn write_ticket_scan(accounts: &TicketCpiAccounts, result: &str) -> Result<()> {
+  write_ticket_app_data(accounts, result.as_bytes().to_vec())?;
+  freeze_ticket(accounts)
+}
+
+/// Replaces the app data written by the community on the ticket.
+pub fn write_ticket_app_data(accounts: &TicketCpiAccounts, data: Vec<u8>) -> Result<()> {
+  let community = accounts.community;
+  let signer_seeds = &[
+    COMMUNITY_PRE_SEED,
+    community.seed.as_ref(),
+    &[community.bump],
+  ];
+
+  WriteExternalPluginAdapterDataV1CpiBuilder::new(accounts.mpl_core_program)
+    .asset(accounts.ticket)
+    .collection(Some(accounts.event_collection))
+    .payer(accounts.payer)
+    .authority(Some(&community.to_account_info()))
+    .system_program(accounts.system_program)
+    .key(ExternalPluginAdapterKey::AppData(
+      PluginAuthority::Address {
+        address: community.key(),
+      },
+    ))
+    .data(data)
+    .invoke_signed(&[signer_seeds])?;
+
+  Ok(())
+}
+
+/// Scanned tickets can no longer be transferred.
+pub fn freeze_ticket(accounts: &TicketCpiAccounts) -> Result<()> {
+  let community = accounts.community;
+  let signer_seeds = &[
+    COMMUNITY_PRE_SEED,
+    community.seed.as_ref(),
+    &[community.bump],
+  ];
+
+  UpdatePluginV1CpiBuilder::new(accounts.mpl_core_program)
+    .asset(accounts.ticket)
+    .collection(Some(accounts.event_collection))
+    .payer(accounts.payer)
+    .authority(Some(&community.to_account_info()))
+    .system_program(accounts.system_program)
+    .plugin(Plugin::PermanentFreezeDelegate(PermanentFreezeDelegate {
+      frozen: true,
+    }))
+    .invoke_signed(&[signer_seeds])?;
+
+  Ok(())
+}
+
 // Custom function to validate nft belongs to a specified collection and is verified
 pub fn validate_nft_collection(nft_metadata: &AccountInfo, collection_mint: Pubkey) -> Result<()> {
   let metadata_data = nft_metadata.try_borrow_data()?;
@@ -191,6 +231,100 @@ pub fn validate_verified_nft_creator(
   }
 }
 
+/// Deserializes an mpl-core asset and checks that it is held by the owner
+pub fn load_core_asset(asset: &AccountInfo, owner: &Pubkey) -> Result<BaseAssetV1> {
+  require_keys_eq!(*asset.owner, MPL_CORE_ID, FoshoErrors::InvalidCoreAsset);
+  let asset_data =
+    BaseAssetV1::try_from(asset).map_err(|_| error!(FoshoErrors::InvalidCoreAsset))?;
+  require!(
+    asset_data.key == CoreKey::AssetV1,
+    FoshoErrors::InvalidCoreAsset
+  );
+  require_keys_eq!(asset_data.owner, *owner, FoshoErrors::InvalidCoreAsset);
+  Ok(asset_data)
+}
+
+// only the collection authority can add an asset to a collection
+pub fn validate_core_asset_collection(asset_data: &BaseAssetV1, collection: &Pubkey) -> Result<()> {
+  match asset_data.update_authority {
+    UpdateAuthority::Collection(asset_collection) if asset_collection == *collection => Ok(()),
+    _ => Err(FoshoErrors::InvalidCollection.into()),
+  }
+}
+
+pub fn validate_core_asset_creator(asset: &AccountInfo, creator: &Pubkey) -> Result<()> {
+  let (_, verified_creators, _) =
+    fetch_asset_plugin::<VerifiedCreators>(asset, PluginType::VerifiedCreators)
+      .map_err(|_| error!(FoshoErrors::NoCreatorsPresentOnMetadata))?;
+  require!(
+    verified_creators
+      .signatures
+      .iter()
+      .any(|signature| signature.address == *creator && signature.verified),
+    FoshoErrors::InvalidCreator
+  );
+  Ok(())
+}
+
+// the attributes must be managed by the update authority, not by the owner
+pub fn validate_core_asset_attribute(asset: &AccountInfo, key: &str, value: &str) -> Result<()> {
+  let (authority, attributes, _) = fetch_asset_plugin::<Attributes>(asset, PluginType::Attributes)
+    .map_err(|_| error!(FoshoErrors::InvalidAssetAttribute))?;
+  require!(
+    authority == PluginAuthority::UpdateAuthority
+      && attributes
+        .attribute_list
+        .iter()
+        .any(|attribute| attribute.key == key && attribute.value == value),
+    FoshoErrors::InvalidAssetAttribute
+  );
+  Ok(())
+}
+
+/// Checks that the instruction preceding the current one verifies the signature
+/// of `signer` over `message` with the Ed25519 program. The signature, the public
+/// key and the message have to be in the data of the Ed25519 instruction.
+pub fn verify_ed25519_signature(
+  instructions_sysvar: &AccountInfo,
+  signer: &Pubkey,
+  message: &[u8],
+) -> Result<()> {
+  let current_index = load_current_index_checked(instructions_sysvar)?;
+  require_gt!(current_index, 0, FoshoErrors::InvalidSignature);
+  let ed25519_instruction =
+    load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+  require!(
+    ed25519_instruction.program_id == ed25519_program::ID
+      && ed25519_instruction.accounts.is_empty(),
+    FoshoErrors::InvalidSignature
+  );
+
+  // signatures count (1), padding (1), then the offsets of a single signature
+  let data = &ed25519_instruction.data;
+  require!(
+    data.len() >= 16 && data[0] == 1,
+    FoshoErrors::InvalidSignature
+  );
+  let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]) as usize;
+  let current_instruction = u16::MAX as usize;
+  require!(
+    read_u16(4) == current_instruction
+      && read_u16(8) == current_instruction
+      && read_u16(14) == current_instruction,
+    FoshoErrors::InvalidSignature
+  );
+
+  let public_key_offset = read_u16(6);
+  let message_offset = read_u16(10);
+  let public_key = data.get(public_key_offset..public_key_offset + PUBKEY_BYTES);
+  let signed_message = data.get(message_offset..message_offset + read_u16(12));
+  require!(
+    public_key == Some(signer.as_ref()) && signed_message == Some(message),
+    FoshoErrors::InvalidSignature
+  );
+  Ok(())
+}
+
 pub fn assert_is_ata(
   ata: &AccountInfo,
   wallet: &Pubkey,
@@ -200,7 +334,7 @@ pub fn assert_is_ata(
 ) -> Result<()> {
   if initialized {
     let ata_data = ata.data.borrow();
-    let ata_account = StateWithExtensions::<Account>::unpack(&ata_data)?;
+    let ata_account = StateWithExtensions::<TokenAccount>::unpack(&ata_data)?;
 
     assert_owned_by(ata, &token_program)?;
     assert_keys_equal(ata_account.base.owner, *wallet)?;
@@ -244,3 +378,106 @@ pub fn get_spl_token_amount(token_account_info: &AccountInfo) -> Result<u64> {
 
   Ok(u64::from_le_bytes(*amount_bytes))
 }
+
+/// Closes an account owned by this program and sends its lamports to the destination
+pub fn close_program_account<'info>(
+  info: &AccountInfo<'info>,
+  sol_destination: &AccountInfo<'info>,
+) -> Result<()> {
+  let dest_starting_lamports = sol_destination.lamports();
+  **sol_destination.lamports.borrow_mut() = dest_starting_lamports
+    .checked_add(info.lamports())
+    .ok_or(FoshoErrors::NumericalOverflow)?;
+  **info.lamports.borrow_mut() = 0;
+
+  info.assign(&system_program::ID);
+  info.realloc(0, false).map_err(Into::into)
+}
+
+/// Transfers tokens out of a token account owned by the event
+pub fn transfer_from_event<'info>(
+  event: &Account<'info, Event>,
+  from: AccountInfo<'info>,
+  to: AccountInfo<'info>,
+  mint: &InterfaceAccount<'info, Mint>,
+  token_program: AccountInfo<'info>,
+  amount: u64,
+) -> Result<()> {
+  let nonce_binding = event.nonce.to_le_bytes();
+  let event_seeds = &[
+    EVENT_PRE_SEED,
+    event.community.as_ref(),
+    nonce_binding.as_ref(),
+    &[event.bump],
+  ];
+
+  let cpi_accounts = TransferChecked {
+    from,
+    to,
+    mint: mint.to_account_info(),
+    authority: event.to_account_info(),
+  };
+
+  transfer_checked(
+    CpiContext::new_with_signer(token_program, cpi_accounts, &[&event_seeds[..]]),
+    amount,
+    mint.decimals,
+  )
+}
+
+/// Transfers tokens out of a token account owned by the fee vault of an event
+pub fn transfer_from_vault<'info>(
+  vault: &Account<'info, EventVault>,
+  from: AccountInfo<'info>,
+  to: AccountInfo<'info>,
+  mint: &InterfaceAccount<'info, Mint>,
+  token_program: AccountInfo<'info>,
+  amount: u64,
+) -> Result<()> {
+  let vault_seeds = &[
+    EVENT_PRE_SEED,
+    vault.event.as_ref(),
+    VAULT_SUFFIX_SEED,
+    &[vault.bump],
+  ];
+
+  let cpi_accounts = TransferChecked {
+    from,
+    to,
+    mint: mint.to_account_info(),
+    authority: vault.to_account_info(),
+  };
+
+  transfer_checked(
+    CpiContext::new_with_signer(token_program, cpi_accounts, &[&vault_seeds[..]]),
+    amount,
+    mint.decimals,
+  )
+}
+
+/// Burns tokens of a token account owned by the fee vault of an event
+pub fn burn_from_vault<'info>(
+  vault: &Account<'info, EventVault>,
+  from: AccountInfo<'info>,
+  mint: &InterfaceAccount<'info, Mint>,
+  token_program: AccountInfo<'info>,
+  amount: u64,
+) -> Result<()> {
+  let vault_seeds = &[
+    EVENT_PRE_SEED,
+    vault.event.as_ref(),
+    VAULT_SUFFIX_SEED,
+    &[vault.bump],
+  ];
+
+  let cpi_accounts = Burn {
+    mint: mint.to_account_info(),
+    from,
+    authority: vault.to_account_info(),
+  };
+
+  burn(
+    CpiContext::new_with_signer(token_program, cpi_accounts, &[&vault_seeds[..]]),
+    amount,
+  )
+}
@@ -10,10 +10,32 @@ import {
   mplCore,
 } from "@metaplex-foundation/mpl-core";
 import { publicKey } from "@metaplex-foundation/umi";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMint,
+  getMintLen,
+  getTransferFeeConfig,
+  mintTo,
+} from "@solana/spl-token";
 import timer from "timers/promises";
 
 const sleep = (ms: number) => timer.setTimeout(ms);
 
+// permission bits of an event authority
+const ROLE_SCANNER = 1 << 0;
+const ROLE_APPROVER = 1 << 1;
+const ROLE_TREASURER = 1 << 2;
+const ROLE_EDITOR = 1 << 3;
+const ALL_ROLES = ROLE_SCANNER | ROLE_APPROVER | ROLE_TREASURER | ROLE_EDITOR;
+
 export function createKnownTestKeypair(knownKey: string) {
   try {
     const deterministicSalt = crypto
@@ -50,6 +72,8 @@ describe("fosho-program", () => {
   const eventAttendee1 = createKnownTestKeypair("eventAttendee1");
   const eventAttendee2 = createKnownTestKeypair("eventAttendee2");
   const eventAttendeeRejected = createKnownTestKeypair("eventAttendeeRejected");
+  // second approver of the community once the governance is enabled
+  const approver = createKnownTestKeypair("approver");
 
   const getEvent = (nonce: number) => {
     const [event] = anchor.web3.PublicKey.findProgramAddressSync(
@@ -102,6 +126,450 @@ describe("fosho-program", () => {
     return attendeeRecord;
   };
 
+  const getVault = (event: anchor.web3.PublicKey) => {
+    const [vault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("event"), event.toBuffer(), Buffer.from("vault")],
+      program.programId
+    );
+
+    return vault;
+  };
+
+  const getAttendanceHistory = (owner: anchor.web3.PublicKey) => {
+    const [attendanceHistory] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("attendance"), community.toBuffer(), owner.toBuffer()],
+      program.programId
+    );
+
+    return attendanceHistory;
+  };
+
+  const getTicketTier = (event: anchor.web3.PublicKey, index: number) => {
+    const [ticketTier] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("event"),
+        event.toBuffer(),
+        Buffer.from("tier"),
+        Buffer.from([index]),
+      ],
+      program.programId
+    );
+
+    return ticketTier;
+  };
+
+  const getSession = (event: anchor.web3.PublicKey, index: number) => {
+    const [session] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("event"),
+        event.toBuffer(),
+        Buffer.from("session"),
+        Buffer.from([index]),
+      ],
+      program.programId
+    );
+
+    return session;
+  };
+
+  // commitment fees paid in tokens instead of SOL
+  type FeeTokens = {
+    mint: anchor.web3.PublicKey;
+    tokenProgram: anchor.web3.PublicKey;
+  };
+
+  const getVaultFeeAccount = (
+    event: anchor.web3.PublicKey,
+    { mint, tokenProgram }: FeeTokens
+  ) => getAssociatedTokenAddressSync(mint, getVault(event), true, tokenProgram);
+
+  const joinEvent = async (
+    event: anchor.web3.PublicKey,
+    attendee: anchor.web3.Keypair,
+    ticketTier: anchor.web3.PublicKey | null = null,
+    remainingAccounts: anchor.web3.AccountMeta[] = [],
+    allowlistProof: any = null,
+    feeTokens: FeeTokens | null = null
+  ) => {
+    const { rewardMint } = await program.account.event.fetch(event);
+    await program.provider.connection.confirmTransaction(
+      await program.provider.connection.requestAirdrop(
+        attendee.publicKey,
+        1 * anchor.web3.LAMPORTS_PER_SOL
+      ),
+      "confirmed"
+    );
+    const joinEventIxn = await program.methods
+      .joinEvent(allowlistProof, null)
+      .accountsPartial({
+        ticketTier,
+        feeMint: feeTokens?.mint ?? null,
+        feeAccount: feeTokens ? getVaultFeeAccount(event, feeTokens) : null,
+        attendeeFeeAccount: feeTokens
+          ? getAssociatedTokenAddressSync(
+              feeTokens.mint,
+              attendee.publicKey,
+              false,
+              feeTokens.tokenProgram
+            )
+          : null,
+        rewardAccount: rewardMint
+          ? getAssociatedTokenAddressSync(rewardMint, event, true)
+          : null,
+        feeTokenProgram: feeTokens?.tokenProgram ?? null,
+        instructionsSysvar: null,
+        invitationRecord: null,
+        community,
+        event,
+        eventAuthority: eventAuthority.publicKey,
+        attendee: attendee.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        mplCoreProgram: new anchor.web3.PublicKey(
+          "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+        ),
+      })
+      .remainingAccounts(remainingAccounts)
+      .instruction();
+
+    // include the eventAuthority as a signer
+    joinEventIxn.keys = joinEventIxn.keys.map((key) =>
+      key.pubkey.equals(eventAuthority.publicKey)
+        ? { pubkey: key.pubkey, isSigner: true, isWritable: false }
+        : key
+    );
+
+    const messageV0 = new anchor.web3.TransactionMessage({
+      instructions: [joinEventIxn],
+      payerKey: attendee.publicKey,
+      recentBlockhash: (await program.provider.connection.getLatestBlockhash())
+        .blockhash,
+    }).compileToV0Message([]);
+
+    const txJoinEvent = new anchor.web3.VersionedTransaction(messageV0);
+    txJoinEvent.sign([attendee, eventAuthority]);
+
+    await program.provider.connection.confirmTransaction(
+      await program.provider.connection.sendRawTransaction(
+        txJoinEvent.serialize()
+      ),
+      "confirmed"
+    );
+  };
+
+  type EventOptions = {
+    eventType?: any;
+    commitmentFee?: number;
+    eventStartsAt?: number;
+    eventEndsAt?: number;
+    eventVersion?: any;
+    roles?: number;
+    feeTokens?: FeeTokens | null;
+    // rewards without a capacity are funded with fund_rewards
+    rewardPerUser?: number;
+    rewardMint?: anchor.web3.PublicKey | null;
+  };
+
+  // creates the next event of the community, authorized for the eventAuthority
+  const createEvent = async (
+    name: string,
+    {
+      eventType = { conference: {} },
+      commitmentFee = 0,
+      eventStartsAt = Date.now() / 1000 + 100,
+      eventEndsAt = Date.now() / 1000 + 200,
+      eventVersion = { regular: {} },
+      roles = ALL_ROLES,
+      feeTokens = null,
+      rewardPerUser = 0,
+      rewardMint = null,
+    }: EventOptions = {}
+  ) => {
+    const { eventsCount } = await program.account.community.fetch(community);
+    const newEvent = getEvent(eventsCount);
+    await program.methods
+      .createEvent(
+        name,
+        "https://example.com/nft.json",
+        eventType,
+        "testOrganizer",
+        new anchor.BN(commitmentFee),
+        new anchor.BN(eventStartsAt),
+        new anchor.BN(eventEndsAt),
+        null,
+        null,
+        null,
+        null,
+        null,
+        null,
+        eventVersion,
+        new anchor.BN(rewardPerUser),
+        [{ key: eventAuthority.publicKey, roles }],
+        true
+      )
+      .accountsPartial({
+        community,
+        authority: program.provider.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        mplCoreProgram: new anchor.web3.PublicKey(
+          "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+        ),
+        rewardAccount: rewardMint
+          ? getAssociatedTokenAddressSync(rewardMint, newEvent, true)
+          : null,
+        rewardMint,
+        senderAccount: null,
+        feeMint: feeTokens?.mint ?? null,
+        feeAccount: feeTokens ? getVaultFeeAccount(newEvent, feeTokens) : null,
+        feeTokenProgram: feeTokens?.tokenProgram ?? null,
+      })
+      .rpc();
+  };
+
+  // updates only the given fields of the event
+  const updateEvent = async (event: anchor.web3.PublicKey, changes: any) => {
+    await program.methods
+      .updateEvent({
+        name: null,
+        uri: null,
+        eventType: null,
+        organizer: null,
+        commitmentFee: null,
+        eventStartsAt: null,
+        eventEndsAt: null,
+        registrationStartsAt: null,
+        registrationEndsAt: null,
+        capacity: null,
+        location: null,
+        virtualLink: null,
+        description: null,
+        eventVersion: null,
+        authorityMustSign: null,
+        refundPolicy: null,
+        forfeitPolicy: null,
+        checkInMode: null,
+        settlementRule: null,
+        checkOutPolicy: null,
+        amendmentWindow: null,
+        ...changes,
+      })
+      .accountsPartial({
+        community,
+        event,
+        authority: program.provider.publicKey,
+        mplCoreProgram: new anchor.web3.PublicKey(
+          "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+        ),
+      })
+      .rpc();
+  };
+
+  const publishEvent = async (event: anchor.web3.PublicKey) => {
+    await program.methods
+      .publishEvent()
+      .accountsPartial({
+        community,
+        event,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+  };
+
+  // proposal of the provider, approved by the approver once the governance is enabled
+  const createApprovedProposal = async (action: any) => {
+    const { proposalsCount } = await program.account.community.fetch(
+      community
+    );
+    const [proposal] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("proposal"),
+        community.toBuffer(),
+        new anchor.BN(proposalsCount).toArrayLike(Buffer, "le", 4),
+      ],
+      program.programId
+    );
+    await program.methods
+      .createProposal(action)
+      .accountsPartial({
+        proposal,
+        community,
+        proposer: program.provider.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .approveProposal()
+      .accountsPartial({
+        proposal,
+        community,
+        approver: approver.publicKey,
+      })
+      .signers([approver])
+      .rpc();
+
+    return proposal;
+  };
+
+  // joins, verifies, settles, claims and closes an event paid in fee tokens
+  const attendFeeTokenEvent = async (
+    name: string,
+    feeTokens: FeeTokens,
+    attendee: anchor.web3.Keypair,
+    commitmentFee: number
+  ) => {
+    const payer = (program.provider as anchor.AnchorProvider).wallet.payer;
+    const { eventsCount } = await program.account.community.fetch(community);
+    const feeEvent = getEvent(eventsCount);
+    const timeNow = Date.now() / 1000;
+    const startsAt = timeNow + 5;
+    const endsAt = timeNow + 8;
+
+    await createEvent(name, {
+      commitmentFee,
+      eventStartsAt: startsAt,
+      eventEndsAt: endsAt,
+      feeTokens,
+    });
+    await publishEvent(feeEvent);
+    const attendeeFeeAccount = await createAssociatedTokenAccount(
+      program.provider.connection,
+      payer,
+      feeTokens.mint,
+      attendee.publicKey,
+      undefined,
+      feeTokens.tokenProgram
+    );
+    await mintTo(
+      program.provider.connection,
+      payer,
+      feeTokens.mint,
+      attendeeFeeAccount,
+      payer,
+      commitmentFee,
+      [],
+      undefined,
+      feeTokens.tokenProgram
+    );
+    await joinEvent(feeEvent, attendee, null, [], null, feeTokens);
+
+    await sleep(Math.max(0, startsAt - Date.now() / 1000) * 1000 + 1_000);
+    await program.methods
+      .startCheckIn()
+      .accountsPartial({
+        community,
+        event: feeEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .verifyAttendee(null)
+      .accountsPartial({
+        community,
+        event: feeEvent,
+        eventAuthority: eventAuthority.publicKey,
+        owner: attendee.publicKey,
+        attendeeRecord: getAttendeeRecord(feeEvent, attendee.publicKey),
+        attendanceHistory: getAttendanceHistory(attendee.publicKey),
+        mplCoreProgram: new anchor.web3.PublicKey(
+          "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+        ),
+        instructionsSysvar: null,
+      })
+      .signers([eventAuthority])
+      .rpc();
+    await sleep(Math.max(0, endsAt - Date.now() / 1000) * 1000 + 1_000);
+    await program.methods
+      .endEvent()
+      .accountsPartial({
+        community,
+        event: feeEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .settleEvent()
+      .accountsPartial({
+        community,
+        event: feeEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    await program.methods
+      .claimRewards()
+      .accountsPartial({
+        community,
+        proposal: null,
+        event: feeEvent,
+        claimer: attendee.publicKey,
+        attendeeRecord: getAttendeeRecord(feeEvent, attendee.publicKey),
+        rewardAccount: null,
+        receiverAccount: null,
+        rewardMint: null,
+        feeMint: feeTokens.mint,
+        feeAccount: getVaultFeeAccount(feeEvent, feeTokens),
+        feeReceiverAccount: attendeeFeeAccount,
+        feeTokenProgram: feeTokens.tokenProgram,
+        forfeitReceiver: null,
+        forfeitReceiverFeeAccount: null,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .signers([attendee])
+      .rpc();
+
+    const proposal = await createApprovedProposal({
+      closeEvent: { event: feeEvent },
+    });
+    await program.methods
+      .closeEvent()
+      .accountsPartial({
+        community,
+        event: feeEvent,
+        authority: program.provider.publicKey,
+        executor: program.provider.publicKey,
+        proposal,
+        rewardAccount: null,
+        receiverAccount: null,
+        rewardMint: null,
+        feeMint: feeTokens.mint,
+        feeAccount: getVaultFeeAccount(feeEvent, feeTokens),
+        feeReceiverAccount: await createAssociatedTokenAccount(
+          program.provider.connection,
+          payer,
+          feeTokens.mint,
+          program.provider.publicKey,
+          undefined,
+          feeTokens.tokenProgram
+        ),
+        feeTokenProgram: feeTokens.tokenProgram,
+        forfeitReceiver: null,
+        forfeitReceiverFeeAccount: null,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        {
+          pubkey: getAttendeeRecord(feeEvent, attendee.publicKey),
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: attendee.publicKey, isSigner: false, isWritable: true },
+      ])
+      .rpc();
+
+    assert.isNull(await program.account.event.fetchNullable(feeEvent));
+    assert.isNull(
+      await program.provider.connection.getAccountInfo(
+        getVaultFeeAccount(feeEvent, feeTokens)
+      )
+    );
+    return (
+      await getAccount(
+        program.provider.connection,
+        attendeeFeeAccount,
+        undefined,
+        feeTokens.tokenProgram
+      )
+    ).amount;
+  };
+
   const event = getEvent(0);
   const attendeeRecord1 = getAttendeeRecord(event, eventAttendee1.publicKey);
 
@@ -119,8 +587,93 @@ describe("fosho-program", () => {
     assert.strictEqual(communityData.seed.toString(), seed.toString());
   });
 
+  it("updates community", async () => {
+    await program.methods
+      .updateCommunity({
+        name: "updatedCommunity",
+        uri: "https://example.com/community.json",
+        logo: null,
+        socialLinks: ["https://x.com/fosho"],
+        membershipFee: null,
+        membershipRequiresApproval: null,
+      })
+      .accountsPartial({ community, authority: program.provider.publicKey })
+      .rpc();
+
+    const communityData = await program.account.community.fetch(community);
+    assert.strictEqual(communityData.name, "updatedCommunity");
+    assert.strictEqual(communityData.uri, "https://example.com/community.json");
+    assert.deepStrictEqual(communityData.socialLinks, ["https://x.com/fosho"]);
+
+    try {
+      await program.methods
+        .updateCommunity({
+          name: "n".repeat(51),
+          uri: null,
+          logo: null,
+          socialLinks: null,
+          membershipFee: null,
+          membershipRequiresApproval: null,
+        })
+        .accountsPartial({ community, authority: program.provider.publicKey })
+        .rpc();
+      assert.fail("name longer than 50 characters was accepted");
+    } catch (e) {
+      assert.ok(e.toString().includes("CommunityNameTooLong"));
+    }
+  });
+
+  it("transfers community authority in two steps", async () => {
+    const newAuthority = anchor.web3.Keypair.generate();
+
+    await program.methods
+      .proposeCommunityAuthority(newAuthority.publicKey)
+      .accountsPartial({
+        community,
+        proposal: null,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .acceptCommunityAuthority()
+      .accountsPartial({ community, newAuthority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+
+    let communityData = await program.account.community.fetch(community);
+    assert.strictEqual(
+      communityData.authority.toString(),
+      newAuthority.publicKey.toString()
+    );
+    assert.isNull(communityData.pendingAuthority);
+
+    // hands the community back for the following tests
+    await program.methods
+      .proposeCommunityAuthority(program.provider.publicKey)
+      .accountsPartial({
+        community,
+        proposal: null,
+        authority: newAuthority.publicKey,
+      })
+      .signers([newAuthority])
+      .rpc();
+    await program.methods
+      .acceptCommunityAuthority()
+      .accountsPartial({ community, newAuthority: program.provider.publicKey })
+      .rpc();
+
+    communityData = await program.account.community.fetch(community);
+    assert.strictEqual(
+      communityData.authority.toString(),
+      program.provider.publicKey.toString()
+    );
+  });
+
+  let eventStartsAt: number;
+
   it("creates event", async () => {
     const timeNow = Date.now() / 1000;
+    eventStartsAt = timeNow + 10;
     const tx = await program.methods
       .createEvent(
         "testEvent",
@@ -129,13 +682,13 @@ describe("fosho-program", () => {
         "testOrganizer",
         new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL),
         // event_starts_at
-        new anchor.BN(timeNow + 2),
+        new anchor.BN(eventStartsAt),
         // event ends_at
         new anchor.BN(timeNow + 100),
         // registration starts_at
         new anchor.BN(timeNow + 1),
         // registration ends at
-        new anchor.BN(timeNow + 100),
+        new anchor.BN(eventStartsAt),
         // capacity
         new anchor.BN(3),
         // location
@@ -146,7 +699,7 @@ describe("fosho-program", () => {
         "testDescription",
         { regular: {} },
         new anchor.BN(0),
-        [eventAuthority.publicKey],
+        [{ key: eventAuthority.publicKey, roles: ALL_ROLES }],
         true
       )
       .accountsPartial({
@@ -159,6 +712,9 @@ describe("fosho-program", () => {
         rewardAccount: null,
         rewardMint: null,
         senderAccount: null,
+        feeMint: null,
+        feeAccount: null,
+        feeTokenProgram: null,
       })
       .rpc();
     
@@ -170,6 +726,8 @@ describe("fosho-program", () => {
       0.1 * anchor.web3.LAMPORTS_PER_SOL
     );
     assert.strictEqual(eventData.authorityMustSign, true);
+    assert.strictEqual(eventData.schedule.capacity.toNumber(), 3);
+    assert.deepStrictEqual(eventData.status, { draft: {} });
     const eventCollection = getEventCollection(event);
     const umi = await createUmi();
     const eventCollectionData = await fetchCollectionV1(
@@ -180,26 +738,156 @@ describe("fosho-program", () => {
     assert.strictEqual(eventCollectionData.name, "testEvent");
   });
 
-  it("joins event - 3 attendees", async () => {
-    // sleep for 2 seconds due to event not starting
-    await sleep(2_000);
-    await program.provider.connection.confirmTransaction(
-      await program.provider.connection.requestAirdrop(
-        eventAttendee1.publicKey,
-        1 * anchor.web3.LAMPORTS_PER_SOL
-      ),
-      "confirmed"
-    );
-    await program.provider.connection.confirmTransaction(
-      await program.provider.connection.requestAirdrop(
-        eventAuthority.publicKey,
-        10 * anchor.web3.LAMPORTS_PER_SOL
-      ),
+  it("updates event", async () => {
+    try {
+      await updateEvent(event, { eventEndsAt: new anchor.BN(eventStartsAt) });
+      assert.fail("event ending when it starts was accepted");
+    } catch (e) {
+      assert.ok(e.toString().includes("InvalidEventEndTime"));
+    }
+    try {
+      await updateEvent(event, {
+        registrationStartsAt: new anchor.BN(eventStartsAt),
+        registrationEndsAt: new anchor.BN(eventStartsAt - 1),
+      });
+      assert.fail("registration ending before it starts was accepted");
+    } catch (e) {
+      assert.ok(e.toString().includes("InvalidRegistrationStartTime"));
+    }
+
+    await program.methods
+      .updateEvent({
+        name: null,
+        uri: null,
+        eventType: null,
+        organizer: null,
+        commitmentFee: null,
+        eventStartsAt: null,
+        eventEndsAt: null,
+        registrationStartsAt: null,
+        registrationEndsAt: null,
+        capacity: new anchor.BN(4),
+        location: "updatedLocation",
+        virtualLink: null,
+        description: null,
+        eventVersion: null,
+        authorityMustSign: null,
+        refundPolicy: null,
+        forfeitPolicy: { proRata: {} },
+        checkInMode: null,
+        settlementRule: null,
+        checkOutPolicy: null,
+        amendmentWindow: null,
+      })
+      .accountsPartial({
+        community,
+        event,
+        authority: program.provider.publicKey,
+        mplCoreProgram: new anchor.web3.PublicKey(
+          "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+        ),
+      })
+      .rpc();
+
+    const umi = await createUmi();
+    const eventCollectionData = await fetchCollectionV1(
+      umi,
+      publicKey(getEventCollection(event))
+    );
+    const attributeList = eventCollectionData.attributes?.attributeList ?? [];
+    const attribute = (key: string) =>
+      attributeList.find((attr) => attr.key === key)?.value;
+    assert.strictEqual(attribute("Capacity"), "4");
+    assert.strictEqual(attribute("Location"), "updatedLocation");
+    assert.strictEqual(attribute("Description"), "testDescription");
+    assert.strictEqual(eventCollectionData.name, "testEvent");
+
+    const eventData = await program.account.event.fetch(event);
+    assert.strictEqual(eventData.schedule.capacity.toNumber(), 4);
+    assert.deepStrictEqual(eventData.forfeitPolicy, { proRata: {} });
+  });
+
+  it("manages event authorities", async () => {
+    const lostDevice = anchor.web3.Keypair.generate().publicKey;
+    const newDevice = anchor.web3.Keypair.generate().publicKey;
+    const accounts = {
+      community,
+      event,
+      authority: program.provider.publicKey,
+    };
+
+    await program.methods
+      .addEventAuthority(lostDevice, ROLE_SCANNER)
+      .accountsPartial(accounts)
+      .rpc();
+
+    try {
+      await program.methods
+        .addEventAuthority(lostDevice, ROLE_SCANNER)
+        .accountsPartial(accounts)
+        .rpc();
+      assert.fail("duplicate event authority was added");
+    } catch (e) {
+      assert.ok(e.toString().includes("DuplicateEventAuthority"));
+    }
+
+    await program.methods
+      .replaceEventAuthority(lostDevice, newDevice)
+      .accountsPartial(accounts)
+      .rpc();
+    await program.methods
+      .removeEventAuthority(newDevice)
+      .accountsPartial(accounts)
+      .rpc();
+
+    const eventData = await program.account.event.fetch(event);
+    assert.deepStrictEqual(
+      eventData.eventAuthorities.map(({ key }) => key.toString()),
+      [eventAuthority.publicKey.toString()]
+    );
+  });
+
+  it("publishes event", async () => {
+    await program.methods
+      .publishEvent()
+      .accountsPartial({
+        community,
+        event,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    const eventData = await program.account.event.fetch(event);
+    assert.deepStrictEqual(eventData.status, { published: {} });
+  });
+
+  it("joins event - 3 attendees", async () => {
+    // sleep for 2 seconds due to event not starting
+    await sleep(2_000);
+    await program.provider.connection.confirmTransaction(
+      await program.provider.connection.requestAirdrop(
+        eventAttendee1.publicKey,
+        1 * anchor.web3.LAMPORTS_PER_SOL
+      ),
+      "confirmed"
+    );
+    await program.provider.connection.confirmTransaction(
+      await program.provider.connection.requestAirdrop(
+        eventAuthority.publicKey,
+        10 * anchor.web3.LAMPORTS_PER_SOL
+      ),
       "confirmed"
     );
     const joinEventIxn = await program.methods
-      .joinEvent()
+      .joinEvent(null, null)
       .accountsPartial({
+        ticketTier: null,
+        feeMint: null,
+        feeAccount: null,
+        attendeeFeeAccount: null,
+        rewardAccount: null,
+        feeTokenProgram: null,
+        instructionsSysvar: null,
+        invitationRecord: null,
         community,
         event,
         eventAuthority: eventAuthority.publicKey,
@@ -209,6 +897,7 @@ describe("fosho-program", () => {
           "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
         ),
       })
+      .remainingAccounts(remainingAccounts)
       .instruction();
 
     // include the eventAuthority as a signer
@@ -249,8 +938,16 @@ describe("fosho-program", () => {
     );
 
     const joinEventIxn2 = await program.methods
-      .joinEvent()
+      .joinEvent(null, null)
       .accountsPartial({
+        ticketTier: null,
+        feeMint: null,
+        feeAccount: null,
+        attendeeFeeAccount: null,
+        rewardAccount: null,
+        feeTokenProgram: null,
+        instructionsSysvar: null,
+        invitationRecord: null,
         community,
         event,
         eventAuthority: eventAuthority.publicKey,
@@ -260,6 +957,7 @@ describe("fosho-program", () => {
           "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
         ),
       })
+      .remainingAccounts(remainingAccounts)
       .instruction();
 
     // include the eventAuthority as a signer
@@ -299,8 +997,16 @@ describe("fosho-program", () => {
       "confirmed"
     );
     const joinEventIxnRejected = await program.methods
-      .joinEvent()
+      .joinEvent(null, null)
       .accountsPartial({
+        ticketTier: null,
+        feeMint: null,
+        feeAccount: null,
+        attendeeFeeAccount: null,
+        rewardAccount: null,
+        feeTokenProgram: null,
+        instructionsSysvar: null,
+        invitationRecord: null,
         community,
         event,
         eventAuthority: eventAuthority.publicKey,
@@ -310,6 +1016,7 @@ describe("fosho-program", () => {
           "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
         ),
       })
+      .remainingAccounts(remainingAccounts)
       .instruction();
 
     // include the eventAuthority as a signer
@@ -388,8 +1095,16 @@ describe("fosho-program", () => {
 
   it("joined attendee cannot rejoin", async () => {
     const rejoinJoinedEventIxn = await program.methods
-      .joinEvent()
+      .joinEvent(null, null)
       .accountsPartial({
+        ticketTier: null,
+        feeMint: null,
+        feeAccount: null,
+        attendeeFeeAccount: null,
+        rewardAccount: null,
+        feeTokenProgram: null,
+        instructionsSysvar: null,
+        invitationRecord: null,
         community,
         event,
         eventAuthority: eventAuthority.publicKey,
@@ -399,6 +1114,7 @@ describe("fosho-program", () => {
           "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
         ),
       })
+      .remainingAccounts(remainingAccounts)
       .instruction();
 
     // include the eventAuthority as a signer
@@ -435,6 +1151,69 @@ describe("fosho-program", () => {
     }
   });
 
+  it("unjoins event", async () => {
+    const eventAttendeeUnjoined = createKnownTestKeypair(
+      "eventAttendeeUnjoined"
+    );
+    await joinEvent(event, eventAttendeeUnjoined);
+    const balanceBefore = await program.provider.connection.getBalance(
+      eventAttendeeUnjoined.publicKey
+    );
+
+    await program.methods
+      .unjoinEvent()
+      .accountsPartial({
+        ticketTier: null,
+        feeMint: null,
+        feeAccount: null,
+        attendeeFeeAccount: null,
+        communityAuthority: program.provider.publicKey,
+        communityAuthorityFeeAccount: null,
+        feeTokenProgram: null,
+        forfeitReceiver: null,
+        forfeitReceiverFeeAccount: null,
+        community,
+        event,
+        attendee: eventAttendeeUnjoined.publicKey,
+        mplCoreProgram: new anchor.web3.PublicKey(
+          "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+        ),
+      })
+      .signers([eventAttendeeUnjoined])
+      .rpc();
+
+    const balanceAfter = await program.provider.connection.getBalance(
+      eventAttendeeUnjoined.publicKey
+    );
+    // the event has not started yet, the full fee is refunded
+    assert.isAbove(
+      balanceAfter - balanceBefore,
+      0.09 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    assert.isNull(
+      await program.account.attendee.fetchNullable(
+        getAttendeeRecord(event, eventAttendeeUnjoined.publicKey)
+      )
+    );
+    const eventData = await program.account.event.fetch(event);
+    assert.strictEqual(eventData.attendeesCount, 3);
+  });
+
+  it("starts check-in", async () => {
+    // wait for the event to start
+    await sleep(Math.max(0, eventStartsAt - Date.now() / 1000) * 1000 + 1_000);
+    await program.methods
+      .startCheckIn()
+      .accountsPartial({
+        community,
+        event,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    const eventData = await program.account.event.fetch(event);
+    assert.deepStrictEqual(eventData.status, { live: {} });
+  });
+
   it("reject attendenace", async () => {
     const rejectAttendanceIxn = await program.methods
       .rejectAttendee()
@@ -481,16 +1260,18 @@ describe("fosho-program", () => {
 
   it("rejected attendenace cannot verify attendance", async () => {
     const verifyAttendanceForRejectedIxn = await program.methods
-      .verifyAttendee()
+      .verifyAttendee(null)
       .accountsPartial({
         community,
         event,
         eventAuthority: eventAuthority.publicKey,
         owner: eventAttendeeRejected.publicKey,
         attendeeRecord: attendeeRecordRejected,
+        attendanceHistory: getAttendanceHistory(eventAttendeeRejected.publicKey),
         mplCoreProgram: new anchor.web3.PublicKey(
           "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
         ),
+        instructionsSysvar: null,
       })
       .instruction();
 
@@ -526,16 +1307,18 @@ describe("fosho-program", () => {
 
   it("verify attendenace", async () => {
     const verifyAttendanceIxn = await program.methods
-      .verifyAttendee()
+      .verifyAttendee(null)
       .accountsPartial({
         community,
         event,
         eventAuthority: eventAuthority.publicKey,
         owner: eventAttendee1.publicKey,
         attendeeRecord: attendeeRecord1,
+        attendanceHistory: getAttendanceHistory(eventAttendee1.publicKey),
         mplCoreProgram: new anchor.web3.PublicKey(
           "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
         ),
+        instructionsSysvar: null,
       })
       .instruction();
 
@@ -591,16 +1374,18 @@ describe("fosho-program", () => {
 
   it("verified attendenace cannot reverify", async () => {
     const verifyAttendanceForVerifiedIxn = await program.methods
-      .verifyAttendee()
+      .verifyAttendee(null)
       .accountsPartial({
         community,
         event,
         eventAuthority: eventAuthority.publicKey,
         owner: eventAttendee1.publicKey,
         attendeeRecord: attendeeRecord1,
+        attendanceHistory: getAttendanceHistory(eventAttendee1.publicKey),
         mplCoreProgram: new anchor.web3.PublicKey(
           "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
         ),
+        instructionsSysvar: null,
       })
       .instruction();
 
@@ -634,17 +1419,62 @@ describe("fosho-program", () => {
     }
   });
 
+  it("ends event", async () => {
+    // the event can only end once its end time has passed
+    await updateEvent(event, {
+      eventEndsAt: new anchor.BN(Math.floor(Date.now() / 1000)),
+    });
+    await sleep(2_000);
+    await program.methods
+      .endEvent()
+      .accountsPartial({
+        community,
+        event,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    const eventData = await program.account.event.fetch(event);
+    assert.deepStrictEqual(eventData.status, { ended: {} });
+  });
+
+  it("settles event", async () => {
+    await program.methods
+      .settleEvent()
+      .accountsPartial({
+        community,
+        event,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    const eventData = await program.account.event.fetch(event);
+    assert.deepStrictEqual(eventData.status, { settled: {} });
+
+    // fees of the rejected and the no-show attendee are shared among verified attendees
+    const vaultData = await program.account.eventVault.fetch(getVault(event));
+    assert.strictEqual(
+      vaultData.forfeitPool.toNumber(),
+      2 * eventData.commitmentFee.toNumber()
+    );
+  });
+
   it("claim rewards", async () => {
     await program.methods
       .claimRewards()
       .accountsPartial({
         community,
+        proposal: null,
         event,
         claimer: eventAttendee1.publicKey,
         attendeeRecord: getAttendeeRecord(event, eventAttendee1.publicKey),
         rewardAccount: null,
         receiverAccount: null,
         rewardMint: null,
+        feeMint: null,
+        feeAccount: null,
+        feeReceiverAccount: null,
+        feeTokenProgram: null,
+        forfeitReceiver: null,
+        forfeitReceiverFeeAccount: null,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
       })
       .signers([eventAttendee1])
@@ -653,6 +1483,13 @@ describe("fosho-program", () => {
       attendeeRecord1
     );
     assert.deepStrictEqual(eventAttendee1Data.status, { claimed: {} });
+
+    // the only verified attendee gets the whole forfeit pool as a bonus
+    const vaultData = await program.account.eventVault.fetch(getVault(event));
+    assert.strictEqual(
+      vaultData.totalForfeited.toString(),
+      vaultData.forfeitPool.toString()
+    );
   });
 
   it("claimed rewards cannot be reclaimed", async () => {
@@ -660,12 +1497,19 @@ describe("fosho-program", () => {
       .claimRewards()
       .accountsPartial({
         community,
+        proposal: null,
         event,
         claimer: eventAttendee1.publicKey,
         attendeeRecord: getAttendeeRecord(event, eventAttendee1.publicKey),
         rewardAccount: null,
         receiverAccount: null,
         rewardMint: null,
+        feeMint: null,
+        feeAccount: null,
+        feeReceiverAccount: null,
+        feeTokenProgram: null,
+        forfeitReceiver: null,
+        forfeitReceiverFeeAccount: null,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
       })
       .instruction();
@@ -705,12 +1549,19 @@ describe("fosho-program", () => {
       .claimRewards()
       .accountsPartial({
         community,
+        proposal: null,
         event,
         claimer: eventAttendee2.publicKey,
         attendeeRecord: getAttendeeRecord(event, eventAttendee2.publicKey),
         rewardAccount: null,
         receiverAccount: null,
         rewardMint: null,
+        feeMint: null,
+        feeAccount: null,
+        feeReceiverAccount: null,
+        feeTokenProgram: null,
+        forfeitReceiver: null,
+        forfeitReceiverFeeAccount: null,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
       })
       .instruction();
@@ -750,6 +1601,7 @@ describe("fosho-program", () => {
       .claimRewards()
       .accountsPartial({
         community,
+        proposal: null,
         event,
         claimer: program.provider.publicKey,
         attendeeRecord: getAttendeeRecord(
@@ -759,6 +1611,12 @@ describe("fosho-program", () => {
         rewardAccount: null,
         receiverAccount: null,
         rewardMint: null,
+        feeMint: null,
+        feeAccount: null,
+        feeReceiverAccount: null,
+        feeTokenProgram: null,
+        forfeitReceiver: null,
+        forfeitReceiverFeeAccount: null,
         tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
       })
       .rpc();
@@ -767,4 +1625,2057 @@ describe("fosho-program", () => {
     );
     assert.deepStrictEqual(attendeeDataRejected.status, { claimed: {} });
   });
+
+  it("claim rewards of no-show attendee by community authority", async () => {
+    await program.methods
+      .claimRewards()
+      .accountsPartial({
+        community,
+        proposal: null,
+        event,
+        claimer: program.provider.publicKey,
+        attendeeRecord: getAttendeeRecord(event, eventAttendee2.publicKey),
+        rewardAccount: null,
+        receiverAccount: null,
+        rewardMint: null,
+        feeMint: null,
+        feeAccount: null,
+        feeReceiverAccount: null,
+        feeTokenProgram: null,
+        forfeitReceiver: null,
+        forfeitReceiverFeeAccount: null,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    const eventData = await program.account.event.fetch(event);
+    assert.strictEqual(eventData.claimedCount, 3);
+    assert.strictEqual(eventData.attendeesCount, 3);
+  });
+
+  it("vault holds the fees still owed", async () => {
+    // lamports sent to the vault by anyone do not break the reconciliation
+    await program.provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: program.provider.publicKey,
+          toPubkey: getVault(event),
+          lamports: 1_000,
+        })
+      )
+    );
+    await program.methods
+      .reconcileVault()
+      .accountsPartial({
+        event,
+        vault: getVault(event),
+        feeMint: null,
+        feeAccount: null,
+        feeTokenProgram: null,
+      })
+      .rpc();
+
+    const vaultData = await program.account.eventVault.fetch(getVault(event));
+    // every attendee claimed, nothing is owed anymore
+    assert.strictEqual(
+      vaultData.totalDeposited.toString(),
+      vaultData.totalRefunded.add(vaultData.totalForfeited).toString()
+    );
+  });
+
+  it("closes event", async () => {
+    const attendees = [eventAttendee1, eventAttendee2, eventAttendeeRejected];
+    await program.methods
+      .closeEvent()
+      .accountsPartial({
+        community,
+        event,
+        authority: program.provider.publicKey,
+        executor: program.provider.publicKey,
+        proposal: null,
+        rewardAccount: null,
+        receiverAccount: null,
+        rewardMint: null,
+        feeMint: null,
+        feeAccount: null,
+        feeReceiverAccount: null,
+        feeTokenProgram: null,
+        forfeitReceiver: null,
+        forfeitReceiverFeeAccount: null,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(
+        attendees.flatMap((attendee) => [
+          {
+            pubkey: getAttendeeRecord(event, attendee.publicKey),
+            isSigner: false,
+            isWritable: true,
+          },
+          { pubkey: attendee.publicKey, isSigner: false, isWritable: true },
+        ])
+      )
+      .rpc();
+
+    assert.isNull(await program.account.event.fetchNullable(event));
+    assert.isNull(
+      await program.account.attendee.fetchNullable(attendeeRecord1)
+    );
+  });
+
+  it("joins event with a ticket tier", async () => {
+    const tieredEvent = getEvent(1);
+    await createEvent("tieredEvent");
+
+    const ticketTier = getTicketTier(tieredEvent, 0);
+    try {
+      await program.methods
+        .createTicketTier(
+          "V".repeat(33),
+          "https://example.com/vip.json",
+          new anchor.BN(0),
+          new anchor.BN(0),
+          new anchor.BN(1),
+          { regular: {} }
+        )
+        .accountsPartial({
+          ticketTier,
+          community,
+          event: tieredEvent,
+          authority: program.provider.publicKey,
+          rewardMint: null,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("name longer than 32 characters was accepted");
+    } catch (e) {
+      assert.ok(e.toString().includes("InvalidTicketTierMetadata"));
+    }
+
+    await program.methods
+      .createTicketTier(
+        "VIP",
+        "https://example.com/vip.json",
+        new anchor.BN(0.2 * anchor.web3.LAMPORTS_PER_SOL),
+        new anchor.BN(0),
+        new anchor.BN(1),
+        { regular: {} }
+      )
+      .accountsPartial({
+        ticketTier,
+        community,
+        event: tieredEvent,
+        authority: program.provider.publicKey,
+        rewardMint: null,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await publishEvent(tieredEvent);
+
+    const tieredAttendee = createKnownTestKeypair("tieredAttendee");
+    await joinEvent(tieredEvent, tieredAttendee, ticketTier);
+
+    const attendeeData = await program.account.attendee.fetch(
+      getAttendeeRecord(tieredEvent, tieredAttendee.publicKey)
+    );
+    assert.strictEqual(attendeeData.tier, 0);
+    assert.strictEqual(
+      attendeeData.commitmentFee.toNumber(),
+      0.2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    const ticketTierData = await program.account.ticketTier.fetch(ticketTier);
+    assert.strictEqual(ticketTierData.attendeesCount, 1);
+
+    const umi = await createUmi();
+    const ticketData = await fetchAssetV1(
+      umi,
+      publicKey(getEventTicketAsset(tieredEvent, tieredAttendee.publicKey))
+    );
+    assert.strictEqual(ticketData.name, "tieredEvent VIP #1");
+    assert.strictEqual(ticketData.uri, "https://example.com/vip.json");
+  });
+
+  it("requires approved proposals once the governance is enabled", async () => {
+    const tieredEvent = getEvent(1);
+
+    await program.methods
+      .setApprovers([program.provider.publicKey, approver.publicKey], 2)
+      .accountsPartial({
+        community,
+        proposal: null,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .cancelEvent()
+        .accountsPartial({
+          community,
+          event: tieredEvent,
+          proposal: null,
+          authority: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("event was cancelled without a proposal");
+    } catch (e) {
+      assert.ok(e.toString().includes("ProposalRequired"));
+    }
+
+    const [proposal] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("proposal"),
+        community.toBuffer(),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 4),
+      ],
+      program.programId
+    );
+    await program.methods
+      .createProposal({ cancelEvent: { event: tieredEvent } })
+      .accountsPartial({
+        proposal,
+        community,
+        proposer: program.provider.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .approveProposal()
+      .accountsPartial({
+        proposal,
+        community,
+        approver: approver.publicKey,
+      })
+      .signers([approver])
+      .rpc();
+
+    await program.methods
+      .cancelEvent()
+      .accountsPartial({
+        community,
+        event: tieredEvent,
+        proposal,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    const eventData = await program.account.event.fetch(tieredEvent);
+    assert.deepStrictEqual(eventData.status, { cancelled: {} });
+    const proposalData = await program.account.proposal.fetch(proposal);
+    assert.isTrue(proposalData.executed);
+  });
+
+  it("claims the fee of a cancelled event only once", async () => {
+    const tieredEvent = getEvent(1);
+    const tieredAttendee = createKnownTestKeypair("tieredAttendee");
+    const claimCancelled = () =>
+      program.methods
+        .claimRewards()
+        .accountsPartial({
+          community,
+          proposal: null,
+          event: tieredEvent,
+          claimer: tieredAttendee.publicKey,
+          attendeeRecord: getAttendeeRecord(
+            tieredEvent,
+            tieredAttendee.publicKey
+          ),
+          rewardAccount: null,
+          receiverAccount: null,
+          rewardMint: null,
+          feeMint: null,
+          feeAccount: null,
+          feeReceiverAccount: null,
+          feeTokenProgram: null,
+          forfeitReceiver: null,
+          forfeitReceiverFeeAccount: null,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .signers([tieredAttendee])
+        .rpc();
+
+    const balanceBefore = await program.provider.connection.getBalance(
+      tieredAttendee.publicKey
+    );
+    await claimCancelled();
+    const balanceAfter = await program.provider.connection.getBalance(
+      tieredAttendee.publicKey
+    );
+    assert.strictEqual(
+      balanceAfter - balanceBefore,
+      0.2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+
+    try {
+      await claimCancelled();
+      assert.fail("the fee of a cancelled event was claimed twice");
+    } catch (e) {
+      assert.ok(e.toString().includes("AlreadyClaimed"));
+    }
+    const vaultData = await program.account.eventVault.fetch(
+      getVault(tieredEvent)
+    );
+    assert.strictEqual(
+      vaultData.totalRefunded.toString(),
+      vaultData.totalDeposited.toString()
+    );
+
+    // the ticket tier is closed with the event, its rent goes to the organizer
+    const ticketTier = getTicketTier(tieredEvent, 0);
+    const closeTieredEvent = async (tierRentReceiver: anchor.web3.PublicKey) =>
+      program.methods
+        .closeEvent()
+        .accountsPartial({
+          community,
+          event: tieredEvent,
+          authority: program.provider.publicKey,
+          executor: program.provider.publicKey,
+          proposal: await createApprovedProposal({
+            closeEvent: { event: tieredEvent },
+          }),
+          rewardAccount: null,
+          receiverAccount: null,
+          rewardMint: null,
+          feeMint: null,
+          feeAccount: null,
+          feeReceiverAccount: null,
+          feeTokenProgram: null,
+          forfeitReceiver: null,
+          forfeitReceiverFeeAccount: null,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          {
+            pubkey: getAttendeeRecord(tieredEvent, tieredAttendee.publicKey),
+            isSigner: false,
+            isWritable: true,
+          },
+          { pubkey: tieredAttendee.publicKey, isSigner: false, isWritable: true },
+          { pubkey: ticketTier, isSigner: false, isWritable: true },
+          { pubkey: tierRentReceiver, isSigner: false, isWritable: true },
+        ])
+        .rpc();
+    try {
+      await closeTieredEvent(tieredAttendee.publicKey);
+      assert.fail("the rent of the ticket tier was sent to an attendee");
+    } catch (e) {
+      assert.ok(e.toString().includes("PublicKeyMismatch"));
+    }
+    await closeTieredEvent(program.provider.publicKey);
+
+    assert.isNull(await program.account.event.fetchNullable(tieredEvent));
+    assert.isNull(await program.account.ticketTier.fetchNullable(ticketTier));
+  });
+
+  it("joins a members-only event", async () => {
+    const membersEvent = getEvent(2);
+    const fee = 0.05 * anchor.web3.LAMPORTS_PER_SOL;
+
+    await program.methods
+      .updateCommunity({
+        name: null,
+        uri: null,
+        logo: null,
+        socialLinks: null,
+        membershipFee: new anchor.BN(fee),
+        membershipRequiresApproval: true,
+      })
+      .accountsPartial({ community, authority: program.provider.publicKey })
+      .rpc();
+
+    await createEvent("membersEvent", { eventVersion: { membersOnly: {} } });
+    await publishEvent(membersEvent);
+
+    const member = createKnownTestKeypair("member");
+    const [memberRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("member"), community.toBuffer(), member.publicKey.toBuffer()],
+      program.programId
+    );
+    const memberAccounts = [
+      { pubkey: memberRecord, isSigner: false, isWritable: false },
+    ];
+    await program.provider.connection.confirmTransaction(
+      await program.provider.connection.requestAirdrop(
+        member.publicKey,
+        1 * anchor.web3.LAMPORTS_PER_SOL
+      ),
+      "confirmed"
+    );
+
+    await program.methods
+      .joinCommunity()
+      .accountsPartial({
+        member: memberRecord,
+        community,
+        communityAuthority: program.provider.publicKey,
+        wallet: member.publicKey,
+      })
+      .signers([member])
+      .rpc();
+    // the fee is escrowed by the member account until the approval
+    const memberRent =
+      await program.provider.connection.getMinimumBalanceForRentExemption(
+        (await program.provider.connection.getAccountInfo(memberRecord)).data
+          .length
+      );
+    assert.strictEqual(
+      await program.provider.connection.getBalance(memberRecord),
+      memberRent + fee
+    );
+    assert.strictEqual(
+      (await program.account.member.fetch(memberRecord)).escrowedFee.toNumber(),
+      fee
+    );
+
+    try {
+      await joinEvent(membersEvent, member, null, memberAccounts);
+      assert.fail("pending member joined a members-only event");
+    } catch (e) {
+      assert.ok(e.toString().includes("NotAMember"));
+    }
+
+    const authorityBalance = await program.provider.connection.getBalance(
+      program.provider.publicKey
+    );
+    await program.methods
+      .approveMember()
+      .accountsPartial({
+        member: memberRecord,
+        community,
+        moderator: null,
+        communityAuthority: program.provider.publicKey,
+        wallet: member.publicKey,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    assert.strictEqual(
+      await program.provider.connection.getBalance(memberRecord),
+      memberRent
+    );
+    // the authority also pays the transaction fee
+    assert.isAbove(
+      await program.provider.connection.getBalance(program.provider.publicKey),
+      authorityBalance
+    );
+    await joinEvent(membersEvent, member, null, memberAccounts);
+
+    const memberData = await program.account.member.fetch(memberRecord);
+    assert.deepStrictEqual(memberData.status, { active: {} });
+    const communityData = await program.account.community.fetch(community);
+    assert.strictEqual(communityData.membersCount, 1);
+    const eventData = await program.account.event.fetch(membersEvent);
+    assert.strictEqual(eventData.attendeesCount, 1);
+
+    const inviteMember = () =>
+      program.methods
+        .inviteMember({ moderator: {} })
+        .accountsPartial({
+          member: memberRecord,
+          community,
+          wallet: member.publicKey,
+          authority: program.provider.publicKey,
+        })
+        .rpc();
+    try {
+      await inviteMember();
+      assert.fail("active member was invited");
+    } catch (e) {
+      assert.ok(e.toString().includes("InvalidMemberStatus"));
+    }
+
+    // removed members can be invited again
+    await program.methods
+      .removeMember()
+      .accountsPartial({
+        member: memberRecord,
+        community,
+        moderator: null,
+        communityAuthority: program.provider.publicKey,
+        wallet: member.publicKey,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    await inviteMember();
+    await program.methods
+      .acceptInvitation()
+      .accountsPartial({
+        member: memberRecord,
+        community,
+        wallet: member.publicKey,
+      })
+      .signers([member])
+      .rpc();
+
+    const invitedData = await program.account.member.fetch(memberRecord);
+    assert.deepStrictEqual(invitedData.status, { active: {} });
+    assert.deepStrictEqual(invitedData.role, { moderator: {} });
+    const invitedCommunityData = await program.account.community.fetch(
+      community
+    );
+    assert.strictEqual(invitedCommunityData.membersCount, 1);
+
+    // moderators review pending applications, rejected applicants are refunded
+    const applicant = createKnownTestKeypair("applicant");
+    const [applicantRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("member"),
+        community.toBuffer(),
+        applicant.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    await program.provider.connection.confirmTransaction(
+      await program.provider.connection.requestAirdrop(
+        applicant.publicKey,
+        1 * anchor.web3.LAMPORTS_PER_SOL
+      ),
+      "confirmed"
+    );
+    await program.methods
+      .joinCommunity()
+      .accountsPartial({
+        member: applicantRecord,
+        community,
+        communityAuthority: program.provider.publicKey,
+        wallet: applicant.publicKey,
+      })
+      .signers([applicant])
+      .rpc();
+    const applicantBalance = await program.provider.connection.getBalance(
+      applicant.publicKey
+    );
+
+    const moderatorRemove = (record, wallet) =>
+      program.methods
+        .removeMember()
+        .accountsPartial({
+          member: record,
+          community,
+          moderator: memberRecord,
+          communityAuthority: program.provider.publicKey,
+          wallet,
+          authority: member.publicKey,
+        })
+        .signers([member])
+        .rpc();
+    try {
+      await moderatorRemove(memberRecord, member.publicKey);
+      assert.fail("moderator removed an active member");
+    } catch (e) {
+      assert.ok(e.toString().includes("NotAModerator"));
+    }
+    await moderatorRemove(applicantRecord, applicant.publicKey);
+
+    const applicantData = await program.account.member.fetch(applicantRecord);
+    assert.deepStrictEqual(applicantData.status, { removed: {} });
+    assert.strictEqual(applicantData.escrowedFee.toNumber(), 0);
+    assert.strictEqual(
+      await program.provider.connection.getBalance(applicant.publicKey),
+      applicantBalance + fee
+    );
+  });
+
+  it("joins an event gated on a past ticket collection", async () => {
+    const gatedEvent = getEvent(3);
+    const tieredEvent = getEvent(1);
+    const tieredAttendee = createKnownTestKeypair("tieredAttendee");
+
+    await createEvent("gatedEvent", {
+      eventVersion: {
+        coreCollectionGated: [{ collection: getEventCollection(tieredEvent) }],
+      },
+    });
+    await publishEvent(gatedEvent);
+
+    const pastTicket = [
+      {
+        pubkey: getEventTicketAsset(tieredEvent, tieredAttendee.publicKey),
+        isSigner: false,
+        isWritable: false,
+      },
+    ];
+
+    try {
+      await joinEvent(
+        gatedEvent,
+        createKnownTestKeypair("notATicketHolder"),
+        null,
+        pastTicket
+      );
+      assert.fail("joined with a ticket owned by someone else");
+    } catch (e) {
+      assert.ok(e.toString().includes("InvalidCoreAsset"));
+    }
+
+    await joinEvent(gatedEvent, tieredAttendee, null, pastTicket);
+    const eventData = await program.account.event.fetch(gatedEvent);
+    assert.strictEqual(eventData.attendeesCount, 1);
+  });
+
+  it("joins an event gated on composable rules", async () => {
+    const rulesEvent = getEvent(4);
+    const createRulesEvent = (rules: any[]) =>
+      createEvent("rulesEvent", { eventVersion: { rules: [rules] } });
+    const pastTickets = {
+      coreCollection: [{ collection: getEventCollection(getEvent(1)) }],
+    };
+
+    // assets picked by the attendee cannot be negated
+    try {
+      await createRulesEvent([{ not: {} }, pastTickets]);
+      assert.fail("negated asset rule was accepted");
+    } catch (e) {
+      assert.ok(e.toString().includes("InvalidGateRules"));
+    }
+
+    // past ticket holders or members
+    await createRulesEvent([{ any: [2] }, pastTickets, { member: {} }]);
+    await publishEvent(rulesEvent);
+
+    const gateAccounts = (wallet: anchor.web3.PublicKey) => {
+      const [memberRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("member"), community.toBuffer(), wallet.toBuffer()],
+        program.programId
+      );
+      return [
+        // placeholder for the asset the attendee does not hold
+        {
+          pubkey: anchor.web3.SystemProgram.programId,
+          isSigner: false,
+          isWritable: false,
+        },
+        { pubkey: memberRecord, isSigner: false, isWritable: false },
+      ];
+    };
+
+    const outsider = createKnownTestKeypair("outsider");
+    try {
+      await joinEvent(
+        rulesEvent,
+        outsider,
+        null,
+        gateAccounts(outsider.publicKey)
+      );
+      assert.fail("outsider joined the event");
+    } catch (e) {
+      assert.ok(e.toString().includes("NoGateRuleSatisfied"));
+    }
+
+    const member = createKnownTestKeypair("member");
+    await joinEvent(rulesEvent, member, null, gateAccounts(member.publicKey));
+    const eventData = await program.account.event.fetch(rulesEvent);
+    assert.strictEqual(eventData.attendeesCount, 1);
+  });
+
+  it("joins an event gated on past attendances", async () => {
+    const loyaltyEvent = getEvent(5);
+
+    await createEvent("loyaltyEvent", {
+      eventVersion: { attendanceGated: [{ minCount: 1, lastEvents: 5 }] },
+    });
+    await publishEvent(loyaltyEvent);
+
+    try {
+      await joinEvent(loyaltyEvent, createKnownTestKeypair("newcomer"));
+      assert.fail("newcomer joined the loyalty event");
+    } catch (e) {
+      assert.ok(e.toString().includes("NotEnoughPastAttendances"));
+    }
+
+    // verified at the first event of the community
+    const historyData = await program.account.attendanceHistory.fetch(
+      getAttendanceHistory(eventAttendee1.publicKey)
+    );
+    assert.strictEqual(historyData.verifiedCount, 1);
+    await joinEvent(loyaltyEvent, eventAttendee1);
+    const eventData = await program.account.event.fetch(loyaltyEvent);
+    assert.strictEqual(eventData.attendeesCount, 1);
+  });
+
+  it("joins an allowlisted event", async () => {
+    const allowlistEvent = getEvent(6);
+    const fee = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+
+    // same tree as the fosho-allowlist crate
+    const sha256 = (...parts: Buffer[]) =>
+      crypto.createHash("sha256").update(Buffer.concat(parts)).digest();
+    const leaf = (wallet: anchor.web3.PublicKey, discountBps: number) => {
+      const discount = Buffer.alloc(2);
+      discount.writeUInt16LE(discountBps);
+      return sha256(
+        Buffer.from([0]),
+        wallet.toBuffer(),
+        Buffer.from([0, 0]),
+        discount
+      );
+    };
+    const hashPair = (left: Buffer, right: Buffer) =>
+      Buffer.compare(left, right) <= 0
+        ? sha256(Buffer.from([1]), left, right)
+        : sha256(Buffer.from([1]), right, left);
+
+    const guest = createKnownTestKeypair("guest");
+    const plusOne = createKnownTestKeypair("plusOne");
+    const outsider = createKnownTestKeypair("allowlistOutsider");
+    const guestLeaf = leaf(guest.publicKey, 5000);
+    const plusOneLeaf = leaf(plusOne.publicKey, 0);
+    const guestProof = {
+      tier: null,
+      discountBps: 5000,
+      proof: [Array.from(plusOneLeaf)],
+    };
+
+    await createEvent("allowlistEvent", {
+      commitmentFee: fee,
+      eventVersion: {
+        allowlist: { merkleRoot: Array.from(hashPair(guestLeaf, plusOneLeaf)) },
+      },
+    });
+    await publishEvent(allowlistEvent);
+
+    try {
+      await joinEvent(allowlistEvent, outsider, null, [], guestProof);
+      assert.fail("outsider joined with the proof of a guest");
+    } catch (e) {
+      assert.ok(e.toString().includes("NotAllowlisted"));
+    }
+
+    await joinEvent(allowlistEvent, guest, null, [], guestProof);
+    const attendeeData = await program.account.attendee.fetch(
+      getAttendeeRecord(allowlistEvent, guest.publicKey)
+    );
+    assert.strictEqual(attendeeData.commitmentFee.toNumber(), fee / 2);
+
+    // the list now only has the outsider
+    await program.methods
+      .setAllowlistRoot(Array.from(leaf(outsider.publicKey, 0)))
+      .accountsPartial({
+        event: allowlistEvent,
+        community,
+        ticketTier: null,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    await joinEvent(allowlistEvent, outsider, null, [], {
+      tier: null,
+      discountBps: 0,
+      proof: [],
+    });
+    const eventData = await program.account.event.fetch(allowlistEvent);
+    assert.strictEqual(eventData.attendeesCount, 2);
+  });
+
+  it("joins with an invitation signed by the event authority", async () => {
+    const invitationEvent = getEvent(7);
+    const timeNow = Date.now() / 1000;
+
+    await createEvent("invitationEvent");
+    await publishEvent(invitationEvent);
+
+    // invitation link usable once by any wallet
+    const invitation = {
+      event: invitationEvent,
+      invitee: null,
+      expiresAt: new anchor.BN(Math.floor(timeNow) + 3600),
+      tier: null,
+      maxUses: 1,
+      nonce: new anchor.BN(42),
+    };
+    // prefix, program id and borsh serialization of the invitation
+    const message = Buffer.concat([
+      Buffer.from("fosho-invitation"),
+      program.programId.toBuffer(),
+      invitationEvent.toBuffer(),
+      Buffer.from([0]),
+      invitation.expiresAt.toArrayLike(Buffer, "le", 8),
+      Buffer.from([0]),
+      new anchor.BN(invitation.maxUses).toArrayLike(Buffer, "le", 2),
+      invitation.nonce.toArrayLike(Buffer, "le", 8),
+    ]);
+    const [invitationRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("invitation"),
+        invitationEvent.toBuffer(),
+        eventAuthority.publicKey.toBuffer(),
+        invitation.nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    const joinWithInvitation = async (attendee: anchor.web3.Keypair) => {
+      await program.provider.connection.confirmTransaction(
+        await program.provider.connection.requestAirdrop(
+          attendee.publicKey,
+          1 * anchor.web3.LAMPORTS_PER_SOL
+        ),
+        "confirmed"
+      );
+      await program.methods
+        .joinEvent(null, invitation)
+        .accountsPartial({
+          ticketTier: null,
+          feeMint: null,
+          feeAccount: null,
+          attendeeFeeAccount: null,
+          rewardAccount: null,
+          feeTokenProgram: null,
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          invitationRecord,
+          community,
+          event: invitationEvent,
+          eventAuthority: eventAuthority.publicKey,
+          attendee: attendee.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          mplCoreProgram: new anchor.web3.PublicKey(
+            "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+          ),
+        })
+        .preInstructions([
+          anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: eventAuthority.secretKey,
+            message,
+          }),
+        ])
+        .signers([attendee])
+        .rpc();
+    };
+
+    await joinWithInvitation(createKnownTestKeypair("invitee"));
+    const recordData = await program.account.invitationRecord.fetch(
+      invitationRecord
+    );
+    assert.strictEqual(recordData.uses, 1);
+
+    try {
+      await joinWithInvitation(createKnownTestKeypair("secondInvitee"));
+      assert.fail("invitation was used more than its max uses");
+    } catch (e) {
+      assert.ok(e.toString().includes("InvitationUsedUp"));
+    }
+  });
+
+  it("verifies attendance with a proof of presence", async () => {
+    const presenceEvent = getEvent(8);
+    const timeNow = Date.now() / 1000;
+    const startsAt = timeNow + 5;
+    const attendee = createKnownTestKeypair("presentAttendee");
+
+    await createEvent("presenceEvent", {
+      eventType: { inPerson: {} },
+      eventStartsAt: startsAt,
+      eventEndsAt: timeNow + 100,
+    });
+    await updateEvent(presenceEvent, {
+      checkInMode: { proofOfPresence: { maxAgeSlots: new anchor.BN(150) } },
+    });
+    await publishEvent(presenceEvent);
+    await joinEvent(presenceEvent, attendee);
+    try {
+      await program.methods
+        .startCheckIn()
+        .accountsPartial({
+          community,
+          event: presenceEvent,
+          authority: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("check-in was opened before the event started");
+    } catch (e) {
+      assert.ok(e.toString().includes("EventHasNotStarted"));
+    }
+    await sleep(Math.max(0, startsAt - Date.now() / 1000) * 1000 + 1_000);
+    await program.methods
+      .startCheckIn()
+      .accountsPartial({
+        community,
+        event: presenceEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    const verifyAttendee = (
+      challenge: { slot: anchor.BN; venueNonce: number[] } | null
+    ) =>
+      program.methods
+        .verifyAttendee(challenge)
+        .accountsPartial({
+          community,
+          event: presenceEvent,
+          eventAuthority: eventAuthority.publicKey,
+          owner: attendee.publicKey,
+          attendeeRecord: getAttendeeRecord(presenceEvent, attendee.publicKey),
+          attendanceHistory: getAttendanceHistory(attendee.publicKey),
+          mplCoreProgram: new anchor.web3.PublicKey(
+            "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+          ),
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([eventAuthority]);
+
+    // staff alone cannot check the attendee in
+    try {
+      await verifyAttendee(null).rpc();
+      assert.fail("attendee was verified without a proof of presence");
+    } catch (e) {
+      assert.ok(e.toString().includes("StaleCheckInChallenge"));
+    }
+
+    // challenge displayed at the venue with a nonce rotated by the scanner,
+    // signed by the attendee for its own attendee record
+    const slot = await program.provider.connection.getSlot();
+    const venueNonce = crypto.randomBytes(32);
+    const challengeMessage = (attendeeRecord: anchor.web3.PublicKey) =>
+      crypto
+        .createHash("sha256")
+        .update(
+          Buffer.concat([
+            Buffer.from("fosho-check-in"),
+            presenceEvent.toBuffer(),
+            attendeeRecord.toBuffer(),
+            new anchor.BN(slot).toArrayLike(Buffer, "le", 8),
+            venueNonce,
+          ])
+        )
+        .digest();
+    const challenge = { slot: new anchor.BN(slot), venueNonce: [...venueNonce] };
+
+    // a challenge signed for another ticket is rejected
+    try {
+      await verifyAttendee(challenge)
+        .preInstructions([
+          anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: attendee.secretKey,
+            message: challengeMessage(
+              getAttendeeRecord(presenceEvent, eventAuthority.publicKey)
+            ),
+          }),
+        ])
+        .rpc();
+      assert.fail("attendee was verified with the challenge of another ticket");
+    } catch (e) {
+      assert.ok(e.toString().includes("InvalidSignature"));
+    }
+
+    await verifyAttendee(challenge)
+      .preInstructions([
+        anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+          privateKey: attendee.secretKey,
+          message: challengeMessage(
+            getAttendeeRecord(presenceEvent, attendee.publicKey)
+          ),
+        }),
+      ])
+      .rpc();
+
+    const attendeeData = await program.account.attendee.fetch(
+      getAttendeeRecord(presenceEvent, attendee.publicKey)
+    );
+    assert.deepStrictEqual(attendeeData.status, { verified: {} });
+  });
+
+  it("verifies and rejects attendees in batches", async () => {
+    const batchEvent = getEvent(9);
+    const timeNow = Date.now() / 1000;
+    const startsAt = timeNow + 5;
+    const attendees = [
+      createKnownTestKeypair("batchAttendee1"),
+      createKnownTestKeypair("batchAttendee2"),
+      createKnownTestKeypair("batchAttendee3"),
+    ];
+
+    await createEvent("batchEvent", {
+      eventType: { inPerson: {} },
+      eventStartsAt: startsAt,
+      eventEndsAt: timeNow + 100,
+      roles: ROLE_SCANNER | ROLE_APPROVER,
+    });
+    await publishEvent(batchEvent);
+    for (const attendee of attendees) {
+      await joinEvent(batchEvent, attendee);
+    }
+    await sleep(Math.max(0, startsAt - Date.now() / 1000) * 1000 + 1_000);
+    await program.methods
+      .startCheckIn()
+      .accountsPartial({
+        community,
+        event: batchEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    const scanEntry = (attendee: anchor.web3.Keypair, history: boolean) => [
+      {
+        pubkey: getAttendeeRecord(batchEvent, attendee.publicKey),
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: getEventTicketAsset(batchEvent, attendee.publicKey),
+        isSigner: false,
+        isWritable: true,
+      },
+      { pubkey: attendee.publicKey, isSigner: false, isWritable: false },
+      ...(history
+        ? [
+            {
+              pubkey: getAttendanceHistory(attendee.publicKey),
+              isSigner: false,
+              isWritable: true,
+            },
+          ]
+        : []),
+    ];
+    const batchAccounts = {
+      community,
+      event: batchEvent,
+      eventAuthority: eventAuthority.publicKey,
+      mplCoreProgram: new anchor.web3.PublicKey(
+        "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+      ),
+    };
+    const skippedEntries = async (signature: string) => {
+      const transaction =
+        await program.provider.connection.getTransaction(signature, {
+          commitment: "confirmed",
+          maxSupportedTransactionVersion: 0,
+        });
+      return transaction.meta.logMessages.filter((log) =>
+        log.includes("Skipped entry")
+      );
+    };
+
+    const verifySignature = await program.methods
+      .verifyAttendees()
+      .accountsPartial(batchAccounts)
+      .remainingAccounts([
+        ...scanEntry(attendees[0], true),
+        ...scanEntry(attendees[1], true),
+      ])
+      .signers([eventAuthority])
+      .rpc({ commitment: "confirmed" });
+    assert.deepStrictEqual(await skippedEntries(verifySignature), []);
+
+    // the verified attendee is skipped instead of failing the batch
+    const rejectSignature = await program.methods
+      .rejectAttendees()
+      .accountsPartial(batchAccounts)
+      .remainingAccounts([
+        ...scanEntry(attendees[1], false),
+        ...scanEntry(attendees[2], false),
+      ])
+      .signers([eventAuthority])
+      .rpc({ commitment: "confirmed" });
+    const skipped = await skippedEntries(rejectSignature);
+    assert.equal(skipped.length, 1);
+    assert.ok(skipped[0].includes("Skipped entry 0: AlreadyVerified"));
+
+    const statuses = await Promise.all(
+      attendees.map(async (attendee) => {
+        const attendeeData = await program.account.attendee.fetch(
+          getAttendeeRecord(batchEvent, attendee.publicKey)
+        );
+        return attendeeData.status;
+      })
+    );
+    assert.deepStrictEqual(statuses, [
+      { verified: {} },
+      { verified: {} },
+      { rejected: {} },
+    ]);
+
+    const history = await program.account.attendanceHistory.fetch(
+      getAttendanceHistory(attendees[0].publicKey)
+    );
+    assert.equal(history.verifiedCount, 1);
+
+    const ticket = await fetchAssetV1(
+      await createUmi(),
+      publicKey(getEventTicketAsset(batchEvent, attendees[2].publicKey))
+    );
+    assert.strictEqual(
+      ticket.appDatas?.[0].data.toString(),
+      Uint8Array.from(Buffer.from("Rejected")).toString()
+    );
+  });
+
+  it("verifies attendance per session", async () => {
+    const sessionEvent = getEvent(10);
+    const timeNow = Date.now() / 1000;
+    const startsAt = timeNow + 5;
+    const attendee = createKnownTestKeypair("sessionAttendee");
+
+    await createEvent("sessionEvent", {
+      eventStartsAt: startsAt,
+      eventEndsAt: timeNow + 100,
+      roles: ROLE_SCANNER,
+    });
+    const createSession = (location: string | null) =>
+      program.methods
+        .createSession(
+          new anchor.BN(startsAt),
+          new anchor.BN(timeNow + 100),
+          location
+        )
+        .accountsPartial({
+          community,
+          event: sessionEvent,
+          authority: program.provider.publicKey,
+        })
+        .rpc();
+    for (const location of ["Hall A", "Hall B", null]) {
+      await createSession(location);
+    }
+    try {
+      await updateEvent(sessionEvent, {
+        settlementRule: { minSessions: { minAttended: 4 } },
+      });
+      assert.fail("settlement rule requires more sessions than the event has");
+    } catch (e) {
+      assert.ok(e.toString().includes("InvalidSettlementRule"));
+    }
+    // attended at least 2 of 3 sessions
+    await updateEvent(sessionEvent, {
+      settlementRule: { minSessions: { minAttended: 2 } },
+    });
+    await publishEvent(sessionEvent);
+    await joinEvent(sessionEvent, attendee);
+    try {
+      await createSession("Hall C");
+      assert.fail("session was added after attendees joined");
+    } catch (e) {
+      assert.ok(e.toString().includes("SessionsLocked"));
+    }
+    await sleep(Math.max(0, startsAt - Date.now() / 1000) * 1000 + 1_000);
+    await program.methods
+      .startCheckIn()
+      .accountsPartial({
+        community,
+        event: sessionEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    const attendeeRecord = getAttendeeRecord(sessionEvent, attendee.publicKey);
+    const verifySession = (index: number) =>
+      program.methods
+        .verifySession()
+        .accountsPartial({
+          community,
+          event: sessionEvent,
+          session: getSession(sessionEvent, index),
+          eventAuthority: eventAuthority.publicKey,
+          owner: attendee.publicKey,
+          attendeeRecord,
+          attendanceHistory: getAttendanceHistory(attendee.publicKey),
+          mplCoreProgram: new anchor.web3.PublicKey(
+            "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+          ),
+        })
+        .signers([eventAuthority])
+        .rpc();
+
+    // a single scan does not verify the attendee
+    try {
+      await program.methods
+        .verifyAttendee(null)
+        .accountsPartial({
+          community,
+          event: sessionEvent,
+          eventAuthority: eventAuthority.publicKey,
+          owner: attendee.publicKey,
+          attendeeRecord,
+          attendanceHistory: getAttendanceHistory(attendee.publicKey),
+          mplCoreProgram: new anchor.web3.PublicKey(
+            "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+          ),
+          instructionsSysvar: null,
+        })
+        .signers([eventAuthority])
+        .rpc();
+      assert.fail("attendee was verified with a single scan");
+    } catch (e) {
+      assert.ok(e.toString().includes("SessionCheckInRequired"));
+    }
+
+    // rejections are left to the approvers
+    try {
+      await program.methods
+        .rejectAttendees()
+        .accountsPartial({
+          community,
+          event: sessionEvent,
+          eventAuthority: eventAuthority.publicKey,
+          mplCoreProgram: new anchor.web3.PublicKey(
+            "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+          ),
+        })
+        .signers([eventAuthority])
+        .rpc();
+      assert.fail("a scanner rejected attendees");
+    } catch (e) {
+      assert.ok(e.toString().includes("MissingEventRole"));
+    }
+
+    await verifySession(0);
+    let attendeeData = await program.account.attendee.fetch(attendeeRecord);
+    assert.deepStrictEqual(attendeeData.status, { pending: {} });
+
+    try {
+      await verifySession(0);
+      assert.fail("session was checked in twice");
+    } catch (e) {
+      assert.ok(e.toString().includes("AlreadyScanned"));
+    }
+
+    // attended sessions cannot be refunded
+    try {
+      await program.methods
+        .unjoinEvent()
+        .accountsPartial({
+          ticketTier: null,
+          feeMint: null,
+          feeAccount: null,
+          attendeeFeeAccount: null,
+          communityAuthority: program.provider.publicKey,
+          communityAuthorityFeeAccount: null,
+          feeTokenProgram: null,
+          forfeitReceiver: null,
+          forfeitReceiverFeeAccount: null,
+          community,
+          event: sessionEvent,
+          attendee: attendee.publicKey,
+          mplCoreProgram: new anchor.web3.PublicKey(
+            "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+          ),
+        })
+        .signers([attendee])
+        .rpc();
+      assert.fail("attendee unjoined after attending a session");
+    } catch (e) {
+      assert.ok(e.toString().includes("AlreadyScanned"));
+    }
+
+    await verifySession(2);
+    attendeeData = await program.account.attendee.fetch(attendeeRecord);
+    assert.deepStrictEqual(attendeeData.status, { verified: {} });
+    assert.equal(attendeeData.sessionsAttended.toNumber(), 0b101);
+
+    const sessionData = await program.account.session.fetch(
+      getSession(sessionEvent, 2)
+    );
+    assert.equal(sessionData.attendeesCount, 1);
+    assert.equal(sessionData.location, null);
+
+    const ticket = await fetchAssetV1(
+      await createUmi(),
+      publicKey(getEventTicketAsset(sessionEvent, attendee.publicKey))
+    );
+    assert.strictEqual(
+      ticket.appDatas?.[0].data.toString(),
+      Uint8Array.from([0b101, 0, 0, 0, 0, 0, 0, 0]).toString()
+    );
+  });
+
+  it("settles attendees who leave early as partially attended", async () => {
+    const checkOutEvent = getEvent(11);
+    const timeNow = Date.now() / 1000;
+    const startsAt = timeNow + 5;
+    const commitmentFee = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+    const leaver = createKnownTestKeypair("checkOutLeaver");
+    const stayer = createKnownTestKeypair("checkOutStayer");
+    const missed = createKnownTestKeypair("checkOutMissed");
+
+    await createEvent("checkOutEvent", {
+      eventType: { workshop: {} },
+      commitmentFee,
+      eventStartsAt: startsAt,
+      eventEndsAt: timeNow + 100,
+      roles: ROLE_SCANNER,
+    });
+    // half of the fee is refunded to attendees staying less than 3 seconds
+    await updateEvent(checkOutEvent, {
+      checkOutPolicy: {
+        minPresence: { minPresence: new anchor.BN(3), partialRefundBps: 5_000 },
+      },
+    });
+    await publishEvent(checkOutEvent);
+    await joinEvent(checkOutEvent, leaver);
+    await joinEvent(checkOutEvent, stayer);
+    await joinEvent(checkOutEvent, missed);
+    await sleep(Math.max(0, startsAt - Date.now() / 1000) * 1000 + 1_000);
+    await program.methods
+      .startCheckIn()
+      .accountsPartial({
+        community,
+        event: checkOutEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    const scanAccounts = (attendee: anchor.web3.Keypair) => ({
+      community,
+      event: checkOutEvent,
+      eventAuthority: eventAuthority.publicKey,
+      owner: attendee.publicKey,
+      attendeeRecord: getAttendeeRecord(checkOutEvent, attendee.publicKey),
+      attendanceHistory: getAttendanceHistory(attendee.publicKey),
+      mplCoreProgram: new anchor.web3.PublicKey(
+        "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+      ),
+    });
+    for (const attendee of [leaver, stayer]) {
+      await program.methods
+        .verifyAttendee(null)
+        .accountsPartial({ ...scanAccounts(attendee), instructionsSysvar: null })
+        .signers([eventAuthority])
+        .rpc();
+    }
+    let vaultData = await program.account.eventVault.fetch(
+      getVault(checkOutEvent)
+    );
+    assert.strictEqual(vaultData.verifiedFees.toNumber(), 0);
+    assert.strictEqual(vaultData.partialRefunds.toNumber(), commitmentFee);
+
+    // the fee of a checked-in attendee is set aside until the check-out
+    try {
+      await program.methods
+        .unjoinEvent()
+        .accountsPartial({
+          ticketTier: null,
+          feeMint: null,
+          feeAccount: null,
+          attendeeFeeAccount: null,
+          communityAuthority: program.provider.publicKey,
+          communityAuthorityFeeAccount: null,
+          feeTokenProgram: null,
+          forfeitReceiver: null,
+          forfeitReceiverFeeAccount: null,
+          community,
+          event: checkOutEvent,
+          attendee: leaver.publicKey,
+          mplCoreProgram: new anchor.web3.PublicKey(
+            "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+          ),
+        })
+        .signers([leaver])
+        .rpc();
+      assert.fail("checked-in attendee unjoined the event");
+    } catch (e) {
+      assert.ok(e.toString().includes("AlreadyScanned"));
+    }
+
+    await program.methods
+      .checkOutAttendee()
+      .accountsPartial(scanAccounts(leaver))
+      .signers([eventAuthority])
+      .rpc();
+    await sleep(3_000);
+    await program.methods
+      .checkOutAttendee()
+      .accountsPartial(scanAccounts(stayer))
+      .signers([eventAuthority])
+      .rpc();
+
+    const leaverData = await program.account.attendee.fetch(
+      getAttendeeRecord(checkOutEvent, leaver.publicKey)
+    );
+    assert.deepStrictEqual(leaverData.status, { partiallyAttended: {} });
+    assert.ok(leaverData.checkedOutAt);
+    const stayerData = await program.account.attendee.fetch(
+      getAttendeeRecord(checkOutEvent, stayer.publicKey)
+    );
+    assert.deepStrictEqual(stayerData.status, { verified: {} });
+    vaultData = await program.account.eventVault.fetch(getVault(checkOutEvent));
+    assert.strictEqual(vaultData.verifiedFees.toNumber(), commitmentFee);
+    assert.strictEqual(vaultData.partialRefunds.toNumber(), commitmentFee / 2);
+
+    // the check-out scan of an attendee who stayed was missed
+    await program.methods
+      .verifyAttendee(null)
+      .accountsPartial({ ...scanAccounts(missed), instructionsSysvar: null })
+      .signers([eventAuthority])
+      .rpc();
+    await program.methods
+      .amendAttendance({ verified: {} })
+      .accountsPartial({
+        community,
+        event: checkOutEvent,
+        attendeeRecord: getAttendeeRecord(checkOutEvent, missed.publicKey),
+        attendanceHistory: getAttendanceHistory(missed.publicKey),
+        ticket: getEventTicketAsset(checkOutEvent, missed.publicKey),
+        authority: program.provider.publicKey,
+        mplCoreProgram: new anchor.web3.PublicKey(
+          "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+        ),
+      })
+      .rpc();
+    const missedData = await program.account.attendee.fetch(
+      getAttendeeRecord(checkOutEvent, missed.publicKey)
+    );
+    assert.deepStrictEqual(missedData.status, { verified: {} });
+    vaultData = await program.account.eventVault.fetch(getVault(checkOutEvent));
+    assert.strictEqual(vaultData.verifiedFees.toNumber(), 2 * commitmentFee);
+    assert.strictEqual(vaultData.partialRefunds.toNumber(), commitmentFee / 2);
+
+    await updateEvent(checkOutEvent, {
+      eventEndsAt: new anchor.BN(Math.floor(Date.now() / 1000)),
+    });
+    await sleep(2_000);
+    await program.methods
+      .endEvent()
+      .accountsPartial({
+        community,
+        event: checkOutEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .settleEvent()
+      .accountsPartial({
+        community,
+        event: checkOutEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    // the rest of the fee goes to the organizer under the default forfeit policy,
+    // who also pays the fee of the two signatures
+    const organizerBalance = await program.provider.connection.getBalance(
+      program.provider.publicKey
+    );
+    const leaverBalance = await program.provider.connection.getBalance(
+      leaver.publicKey
+    );
+    await program.methods
+      .claimRewards()
+      .accountsPartial({
+        community,
+        proposal: null,
+        event: checkOutEvent,
+        claimer: leaver.publicKey,
+        attendeeRecord: getAttendeeRecord(checkOutEvent, leaver.publicKey),
+        rewardAccount: null,
+        receiverAccount: null,
+        rewardMint: null,
+        feeMint: null,
+        feeAccount: null,
+        feeReceiverAccount: null,
+        feeTokenProgram: null,
+        forfeitReceiver: program.provider.publicKey,
+        forfeitReceiverFeeAccount: null,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .signers([leaver])
+      .rpc();
+
+    assert.strictEqual(
+      (await program.provider.connection.getBalance(leaver.publicKey)) -
+        leaverBalance,
+      commitmentFee / 2
+    );
+    assert.strictEqual(
+      (await program.provider.connection.getBalance(
+        program.provider.publicKey
+      )) - organizerBalance,
+      commitmentFee / 2 - 10_000
+    );
+  });
+
+  it("amends scan results and resolves appeals", async () => {
+    const amendedEvent = getEvent(12);
+    const timeNow = Date.now() / 1000;
+    const startsAt = timeNow + 5;
+    const misScanned = createKnownTestKeypair("misScannedAttendee");
+    const appellant = createKnownTestKeypair("appellantAttendee");
+
+    await createEvent("amendedEvent", {
+      eventType: { inPerson: {} },
+      eventStartsAt: startsAt,
+      eventEndsAt: timeNow + 100,
+      roles: ROLE_SCANNER | ROLE_APPROVER,
+    });
+    // scan results can be amended up to a day after the event ends
+    await updateEvent(amendedEvent, {
+      amendmentWindow: new anchor.BN(60 * 60 * 24),
+    });
+    await publishEvent(amendedEvent);
+    await joinEvent(amendedEvent, misScanned);
+    await joinEvent(amendedEvent, appellant);
+    await sleep(Math.max(0, startsAt - Date.now() / 1000) * 1000 + 1_000);
+    await program.methods
+      .startCheckIn()
+      .accountsPartial({
+        community,
+        event: amendedEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    const mplCoreProgram = new anchor.web3.PublicKey(
+      "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+    );
+    await program.methods
+      .verifyAttendee(null)
+      .accountsPartial({
+        community,
+        event: amendedEvent,
+        eventAuthority: eventAuthority.publicKey,
+        owner: misScanned.publicKey,
+        attendeeRecord: getAttendeeRecord(amendedEvent, misScanned.publicKey),
+        attendanceHistory: getAttendanceHistory(misScanned.publicKey),
+        mplCoreProgram,
+        instructionsSysvar: null,
+      })
+      .signers([eventAuthority])
+      .rpc();
+    await program.methods
+      .rejectAttendee()
+      .accountsPartial({
+        community,
+        event: amendedEvent,
+        eventAuthority: eventAuthority.publicKey,
+        owner: appellant.publicKey,
+        attendeeRecord: getAttendeeRecord(amendedEvent, appellant.publicKey),
+        mplCoreProgram,
+      })
+      .signers([eventAuthority])
+      .rpc();
+
+    // the mis-scanned attendee was not there
+    await program.methods
+      .amendAttendance({ rejected: {} })
+      .accountsPartial({
+        community,
+        event: amendedEvent,
+        attendeeRecord: getAttendeeRecord(amendedEvent, misScanned.publicKey),
+        attendanceHistory: getAttendanceHistory(misScanned.publicKey),
+        ticket: getEventTicketAsset(amendedEvent, misScanned.publicKey),
+        authority: eventAuthority.publicKey,
+        mplCoreProgram,
+      })
+      .signers([eventAuthority])
+      .rpc();
+    const misScannedData = await program.account.attendee.fetch(
+      getAttendeeRecord(amendedEvent, misScanned.publicKey)
+    );
+    assert.deepStrictEqual(misScannedData.status, { rejected: {} });
+    const historyData = await program.account.attendanceHistory.fetch(
+      getAttendanceHistory(misScanned.publicKey)
+    );
+    assert.strictEqual(historyData.verifiedCount, 0);
+    const ticket = await fetchAssetV1(
+      await createUmi(),
+      publicKey(getEventTicketAsset(amendedEvent, misScanned.publicKey))
+    );
+    assert.strictEqual(
+      ticket.appDatas?.[0].data.toString(),
+      Uint8Array.from(Buffer.from("Rejected")).toString()
+    );
+
+    const [appeal] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("appeal"),
+        getAttendeeRecord(amendedEvent, appellant.publicKey).toBuffer(),
+      ],
+      program.programId
+    );
+    await program.methods
+      .appealRejection("I was at the door, the scanner was offline")
+      .accountsPartial({
+        appeal,
+        attendeeRecord: getAttendeeRecord(amendedEvent, appellant.publicKey),
+        event: amendedEvent,
+        owner: appellant.publicKey,
+      })
+      .signers([appellant])
+      .rpc();
+    await program.methods
+      .resolveAppeal(true)
+      .accountsPartial({
+        appeal,
+        community,
+        event: amendedEvent,
+        attendeeRecord: getAttendeeRecord(amendedEvent, appellant.publicKey),
+        attendanceHistory: getAttendanceHistory(appellant.publicKey),
+        ticket: getEventTicketAsset(amendedEvent, appellant.publicKey),
+        authority: eventAuthority.publicKey,
+        mplCoreProgram,
+      })
+      .signers([eventAuthority])
+      .rpc();
+
+    const appealData = await program.account.appeal.fetch(appeal);
+    assert.deepStrictEqual(appealData.status, { approved: {} });
+    const appellantData = await program.account.attendee.fetch(
+      getAttendeeRecord(amendedEvent, appellant.publicKey)
+    );
+    assert.deepStrictEqual(appellantData.status, { verified: {} });
+
+    try {
+      await program.methods
+        .resolveAppeal(false)
+        .accountsPartial({
+          appeal,
+          community,
+          event: amendedEvent,
+          attendeeRecord: getAttendeeRecord(amendedEvent, appellant.publicKey),
+          attendanceHistory: getAttendanceHistory(appellant.publicKey),
+          ticket: getEventTicketAsset(amendedEvent, appellant.publicKey),
+          authority: eventAuthority.publicKey,
+          mplCoreProgram,
+        })
+        .signers([eventAuthority])
+        .rpc();
+      assert.fail("appeal was resolved twice");
+    } catch (e) {
+      assert.ok(e.toString().includes("AppealAlreadyResolved"));
+    }
+
+    // the event is over but its scan results can still be amended
+    await updateEvent(amendedEvent, {
+      eventEndsAt: new anchor.BN(Math.floor(Date.now() / 1000) - 1),
+    });
+    await program.methods
+      .endEvent()
+      .accountsPartial({
+        community,
+        event: amendedEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    try {
+      await program.methods
+        .settleEvent()
+        .accountsPartial({
+          community,
+          event: amendedEvent,
+          authority: program.provider.publicKey,
+        })
+        .rpc();
+      assert.fail("event was settled during its amendment window");
+    } catch (e) {
+      assert.ok(e.toString().includes("AmendmentWindowOpen"));
+    }
+  });
+
+  it("refunds commitment fees paid in SPL tokens", async () => {
+    const payer = (program.provider as anchor.AnchorProvider).wallet.payer;
+    const commitmentFee = 1_000_000;
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      payer.publicKey,
+      null,
+      6,
+      undefined,
+      undefined,
+      TOKEN_PROGRAM_ID
+    );
+
+    const refunded = await attendFeeTokenEvent(
+      "splFeeEvent",
+      { mint, tokenProgram: TOKEN_PROGRAM_ID },
+      createKnownTestKeypair("splFeeAttendee"),
+      commitmentFee
+    );
+    assert.strictEqual(Number(refunded), commitmentFee);
+  });
+
+  it("closes events paid in a Token-2022 mint with a transfer fee", async () => {
+    const payer = (program.provider as anchor.AnchorProvider).wallet.payer;
+    const commitmentFee = 1_000_000;
+    // 1% of every transfer is withheld on the receiving account
+    const transferFeeBps = 100;
+    const mint = anchor.web3.Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    await program.provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: payer.publicKey,
+          newAccountPubkey: mint.publicKey,
+          space: mintLen,
+          lamports:
+            await program.provider.connection.getMinimumBalanceForRentExemption(
+              mintLen
+            ),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          mint.publicKey,
+          payer.publicKey,
+          payer.publicKey,
+          transferFeeBps,
+          BigInt(commitmentFee),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(
+          mint.publicKey,
+          6,
+          payer.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [mint]
+    );
+
+    const refunded = await attendFeeTokenEvent(
+      "transferFeeEvent",
+      { mint: mint.publicKey, tokenProgram: TOKEN_2022_PROGRAM_ID },
+      createKnownTestKeypair("transferFeeAttendee"),
+      commitmentFee
+    );
+
+    // the transfer fee is withheld once on the deposit and once on the refund
+    const deposited = commitmentFee - (commitmentFee * transferFeeBps) / 10_000;
+    assert.strictEqual(
+      Number(refunded),
+      deposited - (deposited * transferFeeBps) / 10_000
+    );
+    // the fee withheld on the vault was harvested to the mint before closing it
+    const mintData = await getMint(
+      program.provider.connection,
+      mint.publicKey,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    assert.strictEqual(
+      Number(getTransferFeeConfig(mintData).withheldAmount),
+      commitmentFee - deposited
+    );
+  });
+
+  it("requires approved proposals for the claims of the community authority", async () => {
+    const { eventsCount } = await program.account.community.fetch(community);
+    const noShowEvent = getEvent(eventsCount);
+    const timeNow = Date.now() / 1000;
+    const endsAt = timeNow + 4;
+    const commitmentFee = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+    const noShows = [
+      createKnownTestKeypair("governedNoShow"),
+      createKnownTestKeypair("governedNoShow2"),
+    ];
+
+    await createEvent("noShowEvent", {
+      commitmentFee,
+      eventStartsAt: timeNow + 2,
+      eventEndsAt: endsAt,
+    });
+    await publishEvent(noShowEvent);
+    for (const noShow of noShows) {
+      await joinEvent(noShowEvent, noShow);
+    }
+    await sleep(Math.max(0, timeNow + 2 - Date.now() / 1000) * 1000 + 1_000);
+    await program.methods
+      .startCheckIn()
+      .accountsPartial({
+        community,
+        event: noShowEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+    const endNoShowEvent = () =>
+      program.methods
+        .endEvent()
+        .accountsPartial({
+          community,
+          event: noShowEvent,
+          authority: program.provider.publicKey,
+        })
+        .rpc();
+    try {
+      await endNoShowEvent();
+      assert.fail("event was ended before its end time");
+    } catch (e) {
+      assert.ok(e.toString().includes("EventHasNotEnded"));
+    }
+    await sleep(Math.max(0, endsAt - Date.now() / 1000) * 1000 + 1_000);
+    await endNoShowEvent();
+    await program.methods
+      .settleEvent()
+      .accountsPartial({
+        community,
+        event: noShowEvent,
+        authority: program.provider.publicKey,
+      })
+      .rpc();
+
+    const claimNoShow = (
+      noShow: anchor.web3.Keypair,
+      proposal: anchor.web3.PublicKey | null
+    ) =>
+      program.methods
+        .claimRewards()
+        .accountsPartial({
+          community,
+          proposal,
+          event: noShowEvent,
+          claimer: program.provider.publicKey,
+          attendeeRecord: getAttendeeRecord(noShowEvent, noShow.publicKey),
+          rewardAccount: null,
+          receiverAccount: null,
+          rewardMint: null,
+          feeMint: null,
+          feeAccount: null,
+          feeReceiverAccount: null,
+          feeTokenProgram: null,
+          forfeitReceiver: null,
+          forfeitReceiverFeeAccount: null,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    try {
+      await claimNoShow(noShows[0], null);
+      assert.fail("fee of a no-show was claimed without a proposal");
+    } catch (e) {
+      assert.ok(e.toString().includes("ProposalRequired"));
+    }
+
+    // one proposal covers every forfeited fee of the event
+    const proposal = await createApprovedProposal({
+      claimForfeits: { event: noShowEvent },
+    });
+    for (const noShow of noShows) {
+      await claimNoShow(noShow, proposal);
+      const attendeeData = await program.account.attendee.fetch(
+        getAttendeeRecord(noShowEvent, noShow.publicKey)
+      );
+      assert.deepStrictEqual(attendeeData.status, { claimed: {} });
+    }
+
+    // the proposal stays open until it expires
+    const closeProposal = (proposal: anchor.web3.PublicKey) =>
+      program.methods
+        .closeProposal()
+        .accountsPartial({
+          proposal,
+          community,
+          proposer: program.provider.publicKey,
+        })
+        .rpc();
+    try {
+      await closeProposal(proposal);
+      assert.fail("an open proposal was closed");
+    } catch (e) {
+      assert.ok(e.toString().includes("ProposalStillOpen"));
+    }
+
+    // executed proposals are closed and their rent goes back to the proposer
+    const closeEventProposal = await createApprovedProposal({
+      closeEvent: { event: noShowEvent },
+    });
+    await program.methods
+      .closeEvent()
+      .accountsPartial({
+        community,
+        event: noShowEvent,
+        authority: program.provider.publicKey,
+        executor: program.provider.publicKey,
+        proposal: closeEventProposal,
+        rewardAccount: null,
+        receiverAccount: null,
+        rewardMint: null,
+        feeMint: null,
+        feeAccount: null,
+        feeReceiverAccount: null,
+        feeTokenProgram: null,
+        forfeitReceiver: null,
+        forfeitReceiverFeeAccount: null,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    await closeProposal(closeEventProposal);
+    assert.isNull(
+      await program.account.proposal.fetchNullable(closeEventProposal)
+    );
+  });
+
+  it("caps reward withdrawals at the rewards owed to attendees", async () => {
+    const payer = (program.provider as anchor.AnchorProvider).wallet.payer;
+    const { eventsCount } = await program.account.community.fetch(community);
+    const rewardEvent = getEvent(eventsCount);
+    const rewardPerUser = 1_000;
+    const createRewardMint = () =>
+      createMint(program.provider.connection, payer, payer.publicKey, null, 0);
+    const rewardMint = await createRewardMint();
+    const rewardAccount = getAssociatedTokenAddressSync(
+      rewardMint,
+      rewardEvent,
+      true
+    );
+    const organizerAccount = await createAssociatedTokenAccount(
+      program.provider.connection,
+      payer,
+      rewardMint,
+      payer.publicKey
+    );
+    await mintTo(
+      program.provider.connection,
+      payer,
+      rewardMint,
+      organizerAccount,
+      payer,
+      10 * rewardPerUser
+    );
+
+    await createEvent("rewardEvent", { rewardPerUser, rewardMint });
+
+    // tier rewards are paid in the reward token of the event
+    const otherMint = await createRewardMint();
+    try {
+      await program.methods
+        .createTicketTier(
+          "Sponsored",
+          "https://example.com/sponsored.json",
+          new anchor.BN(0),
+          new anchor.BN(rewardPerUser),
+          null,
+          { regular: {} }
+        )
+        .accountsPartial({
+          ticketTier: getTicketTier(rewardEvent, 0),
+          community,
+          event: rewardEvent,
+          authority: program.provider.publicKey,
+          rewardMint: otherMint,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("tier rewards were set in another token");
+    } catch (e) {
+      assert.ok(e.toString().includes("PublicKeyMismatch"));
+    }
+    await publishEvent(rewardEvent);
+
+    try {
+      await joinEvent(rewardEvent, createKnownTestKeypair("unfundedAttendee"));
+      assert.fail("joined before the rewards were funded");
+    } catch (e) {
+      assert.ok(e.toString().includes("RewardsNotFunded"));
+    }
+    await program.methods
+      .fundRewards(new anchor.BN(2 * rewardPerUser))
+      .accountsPartial({
+        event: rewardEvent,
+        rewardMint,
+        rewardAccount,
+        funderAccount: organizerAccount,
+        funder: program.provider.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    await joinEvent(rewardEvent, createKnownTestKeypair("rewardedAttendee"));
+    const eventData = await program.account.event.fetch(rewardEvent);
+    assert.strictEqual(eventData.rewardLiability.toNumber(), rewardPerUser);
+
+    const withdrawRewards = async (amount: number) =>
+      program.methods
+        .withdrawUnallocatedRewards(new anchor.BN(amount))
+        .accountsPartial({
+          event: rewardEvent,
+          community,
+          rewardMint,
+          rewardAccount,
+          receiverAccount: organizerAccount,
+          proposal: await createApprovedProposal({
+            withdrawRewards: {
+              event: rewardEvent,
+              amount: new anchor.BN(amount),
+            },
+          }),
+          authority: program.provider.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    // the reward of the registered attendee stays in the reward account
+    try {
+      await withdrawRewards(2 * rewardPerUser);
+      assert.fail("withdrew rewards owed to an attendee");
+    } catch (e) {
+      assert.ok(e.toString().includes("RewardsAllocated"));
+    }
+    await withdrawRewards(rewardPerUser);
+    const rewardAccountData = await getAccount(
+      program.provider.connection,
+      rewardAccount
+    );
+    assert.strictEqual(Number(rewardAccountData.amount), rewardPerUser);
+
+    try {
+      await joinEvent(rewardEvent, createKnownTestKeypair("unfundedAttendee"));
+      assert.fail("joined once the unallocated rewards were withdrawn");
+    } catch (e) {
+      assert.ok(e.toString().includes("RewardsNotFunded"));
+    }
+  });
+
+  it("keeps the unrefunded part of unjoin fees for the forfeit pool", async () => {
+    const { eventsCount } = await program.account.community.fetch(community);
+    const proRataEvent = getEvent(eventsCount);
+    const commitmentFee = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+    const attendee = createKnownTestKeypair("proRataUnjoined");
+
+    await createEvent("proRataEvent", { commitmentFee });
+    // half of the fee is refunded until the event starts
+    await updateEvent(proRataEvent, {
+      refundPolicy: {
+        fullRefundCutoff: new anchor.BN(1_000_000),
+        partialRefundBps: 5_000,
+        noRefundWindow: new anchor.BN(0),
+      },
+      forfeitPolicy: { proRata: {} },
+    });
+    await publishEvent(proRataEvent);
+    await joinEvent(proRataEvent, attendee);
+
+    try {
+      await updateEvent(proRataEvent, {
+        refundPolicy: {
+          fullRefundCutoff: new anchor.BN(0),
+          partialRefundBps: 0,
+          noRefundWindow: new anchor.BN(0),
+        },
+      });
+      assert.fail("refund policy was changed after attendees joined");
+    } catch (e) {
+      assert.ok(e.toString().includes("RefundPolicyLocked"));
+    }
+
+    const authorityBalance = await program.provider.connection.getBalance(
+      program.provider.publicKey
+    );
+    await program.methods
+      .unjoinEvent()
+      .accountsPartial({
+        ticketTier: null,
+        feeMint: null,
+        feeAccount: null,
+        attendeeFeeAccount: null,
+        communityAuthority: program.provider.publicKey,
+        communityAuthorityFeeAccount: null,
+        feeTokenProgram: null,
+        forfeitReceiver: null,
+        forfeitReceiverFeeAccount: null,
+        community,
+        event: proRataEvent,
+        attendee: attendee.publicKey,
+        mplCoreProgram: new anchor.web3.PublicKey(
+          "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+        ),
+      })
+      .signers([attendee])
+      .rpc();
+
+    // the community authority does not receive the forfeited half,
+    // it only pays the transaction fee
+    assert.isAtMost(
+      await program.provider.connection.getBalance(program.provider.publicKey),
+      authorityBalance
+    );
+    const vaultData = await program.account.eventVault.fetch(
+      getVault(proRataEvent)
+    );
+    assert.strictEqual(vaultData.totalRefunded.toNumber(), commitmentFee / 2);
+    assert.strictEqual(vaultData.totalForfeited.toNumber(), 0);
+  });
 });